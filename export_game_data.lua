@@ -36,6 +36,9 @@ for k, recipe in pairs(game.player.force.recipes) do
 	recipe_table[prop] = recipe[prop]
   end
   recipe_table["allowed_effects"] = recipe.prototype.allowed_effects
+  recipe_table["maximum_productivity"] = recipe.prototype.maximum_productivity
+  recipe_table["surface_conditions"] = recipe.prototype.surface_conditions
+  recipe_table["emissions_multiplier"] = recipe.prototype.emissions_multiplier
   recipes_table[k] = recipe_table
   num_recipes = num_recipes + 1
 end
@@ -60,6 +63,16 @@ local entity_properties = {
 	"resource_category",
 	--[[ OffshorePump ]]
 	"pumping_speed",
+	--[[ Generator ]]
+	"fluid_usage_per_tick",
+	--[[ Reactor ]]
+	"neighbour_bonus",
+	--[[ FluidWagon ]]
+	"fluid_capacity",
+	--[[ RocketSilo ]]
+	"rocket_parts_required",
+	--[[ Space Age ]]
+	"surface_conditions",
 }
 local entities_table = {}
 local num_entities = 0
@@ -71,6 +84,14 @@ for k, entity in pairs(prototypes.entity) do
 		or entity.type == "resource"
 		or entity.type == "plant"
 		or entity.type == "tree"
+		or entity.type == "boiler"
+		or entity.type == "generator"
+		or entity.type == "solar-panel"
+		or entity.type == "accumulator"
+		or entity.type == "reactor"
+		or entity.type == "cargo-wagon"
+		or entity.type == "fluid-wagon"
+		or entity.type == "beacon"
 	then
 		local entity_table = {}
 		for _, prop in pairs(entity_properties) do
@@ -79,6 +100,35 @@ for k, entity in pairs(prototypes.entity) do
 		if entity.crafting_categories then
 			entity_table["crafting_speed"] = entity.get_crafting_speed()
 		end
+		--[[ emissions per minute at full power, by pollutant ("pollution", "spores") ]]
+		local energy_source = entity.electric_energy_source_prototype or entity.burner_prototype
+		if energy_source and energy_source.emissions_per_joule and entity.energy_usage then
+			local emissions = {}
+			for pollutant, per_joule in pairs(energy_source.emissions_per_joule) do
+				emissions[pollutant] = per_joule * entity.energy_usage * 60 * 60
+			end
+			entity_table["emissions_per_minute"] = emissions
+		end
+		if entity.type == "boiler" or entity.type == "reactor" then
+			entity_table["max_energy_usage"] = entity.get_max_energy_usage()
+		end
+		if entity.type == "generator" or entity.type == "solar-panel" then
+			entity_table["max_energy_production"] = entity.get_max_energy_production()
+		end
+		if entity.type == "cargo-wagon" then
+			entity_table["inventory_size"] = entity.get_inventory_size(defines.inventory.cargo_wagon)
+		end
+		if entity.type == "beacon" then
+			entity_table["distribution_effectivity"] = entity.distribution_effectivity
+			entity_table["distribution_effectivity_bonus_per_quality_level"] = entity.distribution_effectivity_bonus_per_quality_level
+			entity_table["profile"] = entity.profile
+			entity_table["beacon_counter"] = entity.beacon_counter
+			entity_table["allowed_effects"] = entity.allowed_effects
+			entity_table["module_inventory_size"] = entity.get_inventory_size(defines.inventory.beacon_modules)
+		end
+		if entity.type == "accumulator" then
+			entity_table["buffer_capacity"] = entity.electric_energy_source_prototype.buffer_capacity
+		end
 		if entity.type == "resource" or entity.type == "plant" or entity.type == "tree"  then
 			entity_table.mineable_properties = {
 				mining_time = entity.mineable_properties.mining_time,
@@ -92,10 +142,70 @@ for k, entity in pairs(prototypes.entity) do
 	end
 end
 
+--[[ https://lua-api.factorio.com/latest/classes/LuaTechnologyPrototype.html ]]
+local technologies_table = {}
+local num_technologies = 0
+for k, technology in pairs(prototypes.technology) do
+	technologies_table[k] = {
+		name = technology.name,
+		effects = technology.effects,
+		level = technology.level,
+		max_level = technology.max_level,
+	}
+	num_technologies = num_technologies + 1
+end
+
+--[[ https://lua-api.factorio.com/latest/classes/LuaItemPrototype.html ]]
+local items_table = {}
+local num_items = 0
+for k, item in pairs(prototypes.item) do
+	items_table[k] = {
+		name = item.name,
+		spoil_ticks = item.get_spoil_ticks(),
+		spoil_result = item.spoil_result and item.spoil_result.name,
+		weight = item.weight,
+		module_effects = item.module_effects,
+		stack_size = item.stack_size,
+	}
+	num_items = num_items + 1
+end
+
+--[[
+	https://lua-api.factorio.com/latest/classes/LuaSpaceLocationPrototype.html
+	Missing surface properties are filled with their default values.
+--]]
+local planets_table = {}
+for k, location in pairs(prototypes.space_location) do
+	if location.type == "planet" then
+		local properties = {}
+		for name, property in pairs(prototypes.surface_property) do
+			properties[name] = (location.surface_properties or {})[name] or property.default_value
+		end
+		local resources = {}
+		local settings = location.map_gen_settings
+		if settings and settings.autoplace_settings and settings.autoplace_settings.entity then
+			for name, _ in pairs(settings.autoplace_settings.entity.settings or {}) do
+				if prototypes.entity[name] and prototypes.entity[name].type == "resource" then
+					table.insert(resources, name)
+				end
+			end
+		end
+		planets_table[k] = {
+			name = location.name,
+			surface_properties = properties,
+			resources = resources,
+		}
+	end
+end
+
 local data = {
 	recipes = recipes_table,
 	entities = entities_table,
+	technologies = technologies_table,
+	planets = planets_table,
+	items = items_table,
+	rocket_lift_weight = prototypes.utility_constants.rocket_lift_weight,
 }
 
 helpers.write_file("game_data.json", helpers.table_to_json(data))
-game.player.print("Exported "..num_recipes.." recipes and "..num_entities.." entities to %appdata%\\Factorio\\script-output\\game_data.json")
+game.player.print("Exported "..num_recipes.." recipes, "..num_entities.." entities, "..num_items.." items and "..num_technologies.." technologies to %appdata%\\Factorio\\script-output\\game_data.json")
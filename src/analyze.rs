@@ -20,16 +20,20 @@ const REACHABLE_RESOURCES: Option<&[&str]> = Some(&[
 
 const HARVESTABLE_LIQUIDS: &[&str] = &["water", "lava", "heavy-oil", "ammoniacal-solution"];
 
-pub fn reachable_items(info: &Info) -> BTreeSet<ItemName> {
-    let harvestable_resources: BTreeSet<ItemName> = info
-        .game_data
+/// Items that are mined, harvested or pumped rather than crafted.
+pub fn harvestable_resources(info: &Info) -> BTreeSet<ItemName> {
+    info.game_data
         .entities
         .values()
         .filter(|v| v.resource_category.is_some() || v.type_ == "plant" || v.type_ == "tree")
         .flat_map(|v| &v.mineable_properties.as_ref().unwrap().products)
         .map(|v| v.name.clone())
         .chain(HARVESTABLE_LIQUIDS.iter().copied().map(|v| v.into()))
-        .collect();
+        .collect()
+}
+
+pub fn reachable_items(info: &Info) -> BTreeSet<ItemName> {
+    let harvestable_resources = harvestable_resources(info);
     trace!("harvestable_resources: {harvestable_resources:?}\n");
 
     let mut reachable_items: BTreeSet<ItemName> = if let Some(resources) = REACHABLE_RESOURCES {
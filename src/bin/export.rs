@@ -0,0 +1,32 @@
+use {
+    factories::{exporter, exporters, Editor},
+    std::{env, path::PathBuf},
+};
+
+/// Usage: export <snippet name or path> [format]
+fn main() -> anyhow::Result<()> {
+    let mut args = env::args().skip(1);
+    let Some(snippet) = args.next() else {
+        anyhow::bail!(
+            "usage: export <snippet name or path> [{}]",
+            exporters()
+                .iter()
+                .map(|e| e.name())
+                .collect::<Vec<_>>()
+                .join("|")
+        );
+    };
+    let format = args.next().unwrap_or_else(|| "text".into());
+    let exporter = exporter(&format)?;
+
+    let mut path = PathBuf::from(&snippet);
+    if !path.exists() {
+        path = PathBuf::from(format!("snippets/{snippet}.json"));
+    }
+    // Editor::init may change current dir, so resolve the path beforehand.
+    let path = path.canonicalize()?;
+    let mut editor = Editor::init()?;
+    editor.load_snippet(path)?;
+    print!("{}", exporter.export(&editor)?);
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use {
+    crate::{
+        analyze::harvestable_resources, game_data::Recipe, info::Info, machine::Machine,
+        primitives::ItemName, rf,
+    },
+    itertools::Itertools,
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt::Write,
+    },
+};
+
+const INSERTER: &str = "inserter";
+// There is no way to know the actual layout, so assume a short belt line
+// for every solid item that enters or leaves a building.
+const BELT_TILES_PER_CONNECTION: u64 = 4;
+
+/// Items needed to build the solved factory.
+#[derive(Debug, Clone, Default)]
+pub struct BillOfMaterials {
+    /// Buildings, modules and (optionally) logistics, by item name.
+    pub entities: BTreeMap<ItemName, u64>,
+    /// `entities` expanded through recipes down to harvestable resources
+    /// and items that have no recipe.
+    pub raw: BTreeMap<ItemName, f64>,
+}
+
+impl BillOfMaterials {
+    /// Buildings are counted per machine with the count rounded up (see `Machine::buildings`).
+    /// Beacons are not shared between machines. `other_buildings` are buildings that are not
+    /// machines of the snippet, e.g. of power plants.
+    pub fn new<'a>(
+        info: &Info,
+        machines: impl IntoIterator<Item = &'a Machine>,
+        other_buildings: impl IntoIterator<Item = (ItemName, u64)>,
+        include_logistics: bool,
+    ) -> Self {
+        let mut entities = BTreeMap::<ItemName, u64>::new();
+        for (name, count) in other_buildings {
+            *entities.entry(name).or_default() += count;
+        }
+        for machine in machines {
+            if machine.crafter.is_source_or_sink() {
+                continue;
+            }
+            let buildings = machine.buildings();
+            if buildings == 0 {
+                continue;
+            }
+            *entities
+                .entry(machine.crafter.name.as_str().into())
+                .or_default() += buildings;
+            for module in &machine.modules {
+                *entities.entry(module.name.as_str().into()).or_default() += buildings;
+            }
+            for beacon in &machine.beacons {
+                *entities
+                    .entry(beacon.type_.name.as_str().into())
+                    .or_default() += buildings;
+                for module in &beacon.modules {
+                    *entities.entry(module.name.as_str().into()).or_default() += buildings;
+                }
+            }
+            if include_logistics {
+                let solid_connections = machine
+                    .recipe
+                    .ingredients
+                    .iter()
+                    .map(|i| &i.name)
+                    .chain(machine.recipe.products.iter().map(|p| &p.name))
+                    .unique()
+                    .filter(|item| !info.is_fluid(item))
+                    .count() as u64;
+                if solid_connections > 0 {
+                    *entities.entry(INSERTER.into()).or_default() += buildings * solid_connections;
+                    if let Some((_, belt)) = info.belt() {
+                        *entities.entry(belt.clone()).or_default() +=
+                            buildings * solid_connections * BELT_TILES_PER_CONNECTION;
+                    }
+                }
+            }
+        }
+
+        let resources = harvestable_resources(info);
+        let mut raw = BTreeMap::new();
+        for (item, count) in &entities {
+            expand(
+                info,
+                &resources,
+                item,
+                *count as f64,
+                &mut BTreeSet::new(),
+                &mut raw,
+            );
+        }
+        BillOfMaterials { entities, raw }
+    }
+
+    pub fn description(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "Build:").unwrap();
+        for (item, count) in &self.entities {
+            writeln!(out, "{count} × {item}").unwrap();
+        }
+        writeln!(out, "==============================").unwrap();
+        writeln!(out, "Raw resources:").unwrap();
+        for (item, amount) in &self.raw {
+            writeln!(out, "{} × {item}", rf(*amount)).unwrap();
+        }
+        out
+    }
+}
+
+/// Recipe used to make an item when building: the recipe with the same name as the item if it exists,
+/// otherwise the first recipe producing it. Only recipes that pass `Config::recipe_filter` are used.
+///
+/// Recycling recipes and recipes that unpack the item (e.g. emptying a barrel that was filled
+/// by the recipe named after the barrel) are skipped because they can't produce the item on their own.
+pub fn build_recipe<'a>(info: &'a Info, item: &ItemName) -> Option<&'a Recipe> {
+    let produces = |recipe: &Recipe| recipe.products.iter().any(|p| &p.name == item);
+    let is_unpacking = |recipe: &Recipe| {
+        recipe.category == "recycling"
+            || recipe.category == "recycling-or-hand-crafting"
+            || recipe.ingredients.iter().any(|ingredient| {
+                info.game_data
+                    .recipes
+                    .get(&ingredient.name.as_str().into())
+                    .is_some_and(|packing| packing.ingredients.iter().any(|i| &i.name == item))
+            })
+    };
+    info.game_data
+        .recipes
+        .get(&item.as_str().into())
+        .filter(|recipe| produces(recipe) && info.is_recipe_offered(recipe))
+        .or_else(|| {
+            info.game_data
+                .recipes
+                .values()
+                .filter(|recipe| {
+                    produces(recipe) && info.is_recipe_offered(recipe) && !is_unpacking(recipe)
+                })
+                .min_by(|a, b| a.order.cmp(&b.order))
+        })
+}
+
+fn expand(
+    info: &Info,
+    resources: &BTreeSet<ItemName>,
+    item: &ItemName,
+    amount: f64,
+    visiting: &mut BTreeSet<ItemName>,
+    raw: &mut BTreeMap<ItemName, f64>,
+) {
+    let recipe =
+        build_recipe(info, item).filter(|_| !resources.contains(item) && !visiting.contains(item));
+    let Some(recipe) = recipe else {
+        *raw.entry(item.clone()).or_default() += amount;
+        return;
+    };
+    let produced: f64 = recipe
+        .products
+        .iter()
+        .filter(|p| &p.name == item)
+        .map(|p| f64::from(p.amount) * p.probability)
+        .sum();
+    if produced <= 0.0 {
+        *raw.entry(item.clone()).or_default() += amount;
+        return;
+    }
+    visiting.insert(item.clone());
+    for ingredient in &recipe.ingredients {
+        expand(
+            info,
+            resources,
+            &ingredient.name,
+            amount * f64::from(ingredient.amount) / produced,
+            visiting,
+            raw,
+        );
+    }
+    visiting.remove(item);
+}
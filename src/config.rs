@@ -1,8 +1,14 @@
 use {
-    crate::primitives::CrafterName,
+    crate::{
+        primitives::{CrafterName, ItemName},
+        research::ResearchLevels,
+    },
+    anyhow::Context,
     serde::{Deserialize, Serialize},
 };
 
+pub const CONFIG_PATH: &str = "config.toml";
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Config {
     pub furnace_type: CrafterName,
@@ -11,8 +17,119 @@ pub struct Config {
     // 1, 2, 3
     #[serde(default = "default_module_tier")]
     pub module_tier: u32,
+
+    // Belt used to annotate flowchart edges, e.g. "transport-belt". Defaults to the fastest belt.
+    #[serde(default)]
+    pub belt_type: Option<ItemName>,
+    #[serde(default)]
+    pub edge_allocation: EdgeAllocation,
+    // Unit used to display and enter rates, e.g. "per-minute" or { belt = "transport-belt" }.
+    #[serde(default)]
+    pub rate_unit: RateUnit,
+    // Current research, e.g. `mining-productivity-3 = 5` or `steel-plate-productivity = 2`.
+    #[serde(default)]
+    pub research: ResearchLevels,
+    // Recipes offered in menus and used to build production chains, e.g. "unlocked".
+    #[serde(default)]
+    pub recipe_filter: RecipeFilter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecipeFilter {
+    #[default]
+    All,
+    /// Only recipes that are enabled in game data or unlocked by the research.
+    Unlocked,
+    /// Like `Unlocked`, but menus also show locked recipes greyed out.
+    ShowLocked,
+}
+
+impl RecipeFilter {
+    pub const ALL: [RecipeFilter; 3] = [
+        RecipeFilter::All,
+        RecipeFilter::Unlocked,
+        RecipeFilter::ShowLocked,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RecipeFilter::All => "All recipes",
+            RecipeFilter::Unlocked => "Only unlocked",
+            RecipeFilter::ShowLocked => "Unlocked, show locked greyed out",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RateUnit {
+    #[default]
+    PerSecond,
+    PerMinute,
+    PerHour,
+    /// Fraction of a belt's throughput. Fluids are still shown per second.
+    Belt(ItemName),
+}
+
+impl RateUnit {
+    /// Number of units in 1/s, or `None` for belts.
+    pub fn per_second_factor(&self) -> Option<f64> {
+        match self {
+            RateUnit::PerSecond => Some(1.0),
+            RateUnit::PerMinute => Some(60.0),
+            RateUnit::PerHour => Some(3600.0),
+            RateUnit::Belt(_) => None,
+        }
+    }
+
+    pub fn suffix(&self) -> &str {
+        match self {
+            RateUnit::PerSecond => "/s",
+            RateUnit::PerMinute => "/min",
+            RateUnit::PerHour => "/h",
+            RateUnit::Belt(belt) => belt.as_str(),
+        }
+    }
+}
+
+/// How the flowchart splits a producer's output between consumers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EdgeAllocation {
+    /// Match producers and consumers so that the number of edges is minimal.
+    #[default]
+    MinEdges,
+    /// Split every producer's output between all consumers proportionally to their demand.
+    Even,
 }
 
 fn default_module_tier() -> u32 {
     1
 }
+
+/// Sets a top-level value in the config file. Other lines, including comments, are kept as they are.
+pub fn save_value(key: &str, value: &impl Serialize) -> anyhow::Result<()> {
+    let value = toml::Value::try_from(value)
+        .with_context(|| format!("failed to serialize {key} for {CONFIG_PATH}"))?;
+    let new_line = format!("{key} = {value}");
+    let text = fs_err::read_to_string(CONFIG_PATH)?;
+    let mut lines = text
+        .lines()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+    // Top-level keys must go before the first table.
+    let tables_start = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let existing = lines[..tables_start]
+        .iter()
+        .position(|line| line.split('=').next().map(str::trim) == Some(key));
+    match existing {
+        Some(index) => lines[index] = new_line,
+        None => lines.insert(tables_start, new_line),
+    }
+    fs_err::write(CONFIG_PATH, lines.join("\n") + "\n")?;
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use {
+    crate::{
+        analyze::harvestable_resources, editor::Editor, machine::Machine, primitives::ItemName,
+    },
+    itertools::Itertools,
+    nalgebra::DMatrix,
+    std::collections::{BTreeMap, BTreeSet},
+    tracing::warn,
+};
+
+/// Resources needed to craft one unit of an item.
+#[derive(Debug, Clone, Default)]
+pub struct ItemCost {
+    /// Raw resources consumed per unit.
+    pub raw: BTreeMap<ItemName, f64>,
+    /// Total time spent by all crafters in the chain per unit, in machine-seconds.
+    pub crafting_time: f64,
+    /// Total energy consumed by all crafters in the chain per unit, in joules.
+    pub energy: f64,
+}
+
+impl ItemCost {
+    pub fn total_raw(&self) -> f64 {
+        self.raw.values().sum()
+    }
+}
+
+struct CraftStep {
+    machine: Machine,
+    /// Per craft. Positive for products and negative for ingredients.
+    item_amounts: BTreeMap<ItemName, f64>,
+    crafts_per_second: f64,
+}
+
+/// Computes the cost of each of `targets` using recipe choices and modules of the current snippet
+/// (see `Editor::preferred_machine`).
+///
+/// Every crafted item in the production chain gets a variable for the number of crafts of its
+/// recipe, so recipes that consume their own products (e.g. Kovarex enrichment) are resolved
+/// by the linear solver instead of recursion. Byproducts are not credited.
+pub fn item_costs(editor: &Editor, targets: &BTreeSet<ItemName>) -> BTreeMap<ItemName, ItemCost> {
+    let resources = harvestable_resources(editor.info());
+
+    // Collect the production chain of all targets.
+    let mut steps = BTreeMap::<ItemName, CraftStep>::new();
+    let mut raw_items = BTreeSet::new();
+    let mut queue = targets.iter().cloned().collect_vec();
+    while let Some(item) = queue.pop() {
+        if steps.contains_key(&item) || raw_items.contains(&item) {
+            continue;
+        }
+        let machine = if resources.contains(&item) {
+            None
+        } else {
+            editor.preferred_machine(&item)
+        };
+        let Some(machine) = machine else {
+            raw_items.insert(item);
+            continue;
+        };
+        let crafts_per_second = f64::from(machine.crafts_per_second());
+        if crafts_per_second <= 0.0 {
+            raw_items.insert(item);
+            continue;
+        }
+        let mut item_amounts = BTreeMap::<ItemName, f64>::new();
+        for item_speed in machine.item_speeds() {
+            *item_amounts.entry(item_speed.item).or_default() +=
+                f64::from(item_speed.speed) / crafts_per_second;
+        }
+        for ingredient in &machine.recipe.ingredients {
+            queue.push(ingredient.name.clone());
+        }
+        steps.insert(
+            item,
+            CraftStep {
+                machine,
+                item_amounts,
+                crafts_per_second,
+            },
+        );
+    }
+
+    // m[j][i] = net amount of item j per craft of the recipe chosen for item i,
+    // ignoring other products of that recipe.
+    let crafted = steps.keys().cloned().collect_vec();
+    let m = DMatrix::from_fn(crafted.len(), crafted.len(), |row, col| {
+        let amount = steps[&crafted[col]]
+            .item_amounts
+            .get(&crafted[row])
+            .copied()
+            .unwrap_or(0.0);
+        if row == col || amount < 0.0 {
+            amount
+        } else {
+            0.0
+        }
+    });
+    let crafts = if crafted.is_empty() {
+        DMatrix::zeros(0, 0)
+    } else if let Some(inverse) = m.clone().try_inverse() {
+        inverse
+    } else {
+        warn!("production chain matrix is singular, item costs are approximate");
+        m.pseudo_inverse(1e-9)
+            .unwrap_or_else(|_| DMatrix::zeros(crafted.len(), crafted.len()))
+    };
+
+    targets
+        .iter()
+        .map(|target| {
+            let mut cost = ItemCost::default();
+            if let Some(target_index) = crafted.iter().position(|i| i == target) {
+                for (i, item) in crafted.iter().enumerate() {
+                    let num_crafts = crafts[(i, target_index)];
+                    if num_crafts.abs() < 1e-12 {
+                        continue;
+                    }
+                    let step = &steps[item];
+                    for (ingredient, amount) in &step.item_amounts {
+                        if *amount < 0.0 && raw_items.contains(ingredient) {
+                            *cost.raw.entry(ingredient.clone()).or_default() -= num_crafts * amount;
+                        }
+                    }
+                    let machine_seconds = num_crafts / step.crafts_per_second;
+                    cost.crafting_time += machine_seconds;
+                    cost.energy += machine_seconds * step.machine.power_usage();
+                }
+            } else {
+                cost.raw.insert(target.clone(), 1.0);
+            }
+            (target.clone(), cost)
+        })
+        .collect()
+}
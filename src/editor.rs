@@ -1,16 +1,35 @@
 use {
     crate::{
+        bom::build_recipe,
+        config::{self, RateUnit, RecipeFilter},
+        game_data::Recipe,
         info::Info,
-        machine::{Beacon, Machine, ModuleType},
+        machine::{format_emissions, Beacon, Machine, Module, ModuleType},
         module_counts,
-        primitives::{CrafterName, ItemName, MachineCount, ModuleName, RecipeName, Speed},
+        optimizer::Optimization,
+        power::{self, format_power, PowerSource},
+        primitives::{
+            BeaconName, CrafterName, ItemName, MachineCount, ModuleName, PlanetName, RecipeName,
+            Speed, TechnologyName,
+        },
+        rational::{format_rational, solve_exact, to_rational},
+        research::{combined_levels, ResearchBonuses, ResearchLevels},
         rf,
-        snippet::{BeaconSnippet, CrafterSnippet, MachineSnippet, Snippet, SourceSinkSnippet},
+        rocket::{self, ROCKET_PART},
+        sensitivity::{self, Sensitivity, Target},
+        snippet::{
+            BeaconSnippet, CrafterSnippet, ItemPolicy, MachineSnippet, Snippet, SourceSinkSnippet,
+        },
+        spoilage,
+        train::{DEFAULT_ROUND_TRIP_TIME, DEFAULT_WAGONS_PER_TRAIN},
     },
     anyhow::{bail, ensure, format_err, Context},
     fallible_iterator::{FallibleIterator, IteratorExt},
     itertools::Itertools,
     nalgebra::{DMatrix, DVector},
+    num_rational::BigRational,
+    num_traits::{Signed, ToPrimitive},
+    ordered_float::OrderedFloat,
     std::{
         collections::{BTreeMap, BTreeSet},
         fmt::Write,
@@ -23,6 +42,8 @@ use {
 pub struct EditorMachine {
     snippet: MachineSnippet,
     machine: Machine,
+    // Solved count as a fraction, only in exact mode.
+    exact_count: Option<BigRational>,
 }
 
 impl EditorMachine {
@@ -33,24 +54,124 @@ impl EditorMachine {
     pub fn machine(&self) -> &Machine {
         &self.machine
     }
+
+    /// Solved count as a fraction if exact mode is on and the exact solve succeeded.
+    pub fn exact_count(&self) -> Option<&BigRational> {
+        self.exact_count.as_ref()
+    }
+
+    /// Solved count as a fraction (e.g. "37/3") in exact mode, otherwise rounded.
+    pub fn count_text(&self) -> String {
+        match &self.exact_count {
+            Some(count) => format_rational(count),
+            None => rf(self.machine.crafter_count).to_string(),
+        }
+    }
+
+    /// Solved count rounded up to whole buildings.
+    pub fn buildings(&self) -> u64 {
+        self.machine.buildings()
+    }
+
+    /// Fraction of time the buildings are working, from 0 to 1.
+    pub fn utilization(&self) -> f64 {
+        let buildings = self.buildings();
+        if buildings == 0 {
+            0.0
+        } else {
+            self.machine.crafter_count / buildings as f64
+        }
+    }
+}
+
+// Solver output is not exact, so 3.0000001 should still mean 3 buildings.
+pub const BUILDING_COUNT_EPSILON: f64 = 1e-6;
+
+// Time an item spends on belts and in inserters between two machines, in seconds.
+pub const DEFAULT_TRANSIT_TIME: f64 = 5.0;
+
+/// Estimate of what the whole buildings of the snippet could do at full speed, beyond
+/// the solved rates. The solved rates are scaled proportionally, see `Editor::spare_capacity`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpareCapacity {
+    /// Multiplier of the solved rates that fully uses the most loaded row of buildings.
+    pub scale: f64,
+    /// Extra output rates.
+    pub outputs: BTreeMap<ItemName, Speed>,
+    /// Extra input rates needed for the extra outputs.
+    pub inputs: BTreeMap<ItemName, Speed>,
 }
 
-#[derive(Debug)]
+/// Value that limits the scale in `Editor::fit_to`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScaleLimit {
+    /// Speed of an input of the snippet (a source).
+    Input { item: ItemName, speed: Speed },
+    /// Count of a crafter. In integer mode the count of whole buildings is limited.
+    MachineCount {
+        machine_index: usize,
+        count: MachineCount,
+    },
+}
+
+#[derive(Debug, Clone)]
 pub struct Editor {
     info: Info,
     machines: Vec<EditorMachine>,
     item_speed_constraints: BTreeMap<ItemName, Speed>,
+    input_caps: BTreeMap<ItemName, Speed>,
+    item_policies: BTreeMap<ItemName, ItemPolicy>,
+    integer_machine_counts: bool,
+    exact_machine_counts: bool,
+    planned_research: ResearchLevels,
+    use_planned_research: bool,
+    research_bonuses: ResearchBonuses,
+    planet: Option<PlanetName>,
+    transit_time: Option<f64>,
+    max_latency: Option<f64>,
+    rocket_shipments: BTreeMap<ItemName, Speed>,
+    power_plants: BTreeMap<PowerSource, f64>,
+    train_round_trip_time: Option<f64>,
+    wagons_per_train: Option<u32>,
+    cargo_wagon: Option<ItemName>,
+    fluid_wagon: Option<ItemName>,
     solved: bool,
+    // Input caps that limit the solution
+    limiting_input_caps: Vec<ItemName>,
+    // Only in integer mode
+    spare_capacity: Option<SpareCapacity>,
+    sensitivity: Sensitivity,
 }
 
 impl Editor {
     pub fn init() -> anyhow::Result<Self> {
-        Ok(Editor {
+        let mut editor = Editor {
             info: Info::load()?,
             machines: Vec::new(),
             item_speed_constraints: Default::default(),
+            input_caps: Default::default(),
+            item_policies: Default::default(),
+            integer_machine_counts: false,
+            exact_machine_counts: false,
+            planned_research: Default::default(),
+            use_planned_research: false,
+            research_bonuses: Default::default(),
+            planet: None,
+            transit_time: None,
+            max_latency: None,
+            rocket_shipments: Default::default(),
+            power_plants: Default::default(),
+            train_round_trip_time: None,
+            wagons_per_train: None,
+            cargo_wagon: None,
+            fluid_wagon: None,
             solved: true,
-        })
+            limiting_input_caps: Vec::new(),
+            spare_capacity: None,
+            sensitivity: Sensitivity::default(),
+        };
+        editor.update_research();
+        Ok(editor)
     }
 
     fn create_crafter(&self, snippet: &CrafterSnippet) -> anyhow::Result<Machine> {
@@ -84,27 +205,70 @@ impl Editor {
             .beacons
             .iter()
             .map(|beacon| {
-                beacon
-                    .modules
-                    .iter()
-                    .map(|name| self.info.module(name))
-                    .transpose_into_fallible()
-                    .cloned()
-                    .collect()
+                anyhow::Ok(Beacon {
+                    type_: self.info.beacon(beacon.beacon.as_ref())?.clone(),
+                    quality: beacon.quality,
+                    modules: beacon
+                        .modules
+                        .iter()
+                        .map(|name| self.info.module(name))
+                        .transpose_into_fallible()
+                        .cloned()
+                        .collect()?,
+                })
             })
             .transpose_into_fallible()
-            .map(|modules| Ok(Beacon { modules }))
             .collect()?;
 
+        let research =
+            self.research_bonuses
+                .machine_bonus(&self.info.game_data, &crafter.name, &recipe.name);
         Ok(Machine {
             crafter,
             crafter_count: 1.0,
             modules,
             beacons,
             recipe,
+            research,
         })
     }
 
+    /// Machine that would be used to craft the item: the crafter from the snippet if it produces the item,
+    /// otherwise a new crafter without modules. Returns `None` if the item is an input of the snippet
+    /// or can't be crafted.
+    pub fn preferred_machine(&self, item: &ItemName) -> Option<Machine> {
+        for machine in &self.machines {
+            if machine
+                .machine
+                .recipe
+                .products
+                .iter()
+                .any(|p| &p.name == item)
+            {
+                if machine.machine.crafter.is_source() {
+                    return None;
+                }
+                let mut machine = machine.machine.clone();
+                machine.crafter_count = 1.0;
+                return Some(machine);
+            }
+        }
+        let recipe = build_recipe(&self.info, item)?;
+        let crafters = self.info.category_to_crafter.get(&recipe.category)?;
+        let crafter = self
+            .info
+            .auto_select_crafter(crafters)
+            .or_else(|| crafters.first().cloned())?;
+        self.create_crafter(&CrafterSnippet {
+            crafter,
+            modules: vec![],
+            beacons: vec![],
+            recipe: recipe.name.clone(),
+            count_constraint: None,
+        })
+        .ok()
+    }
+
     fn create_machine(&self, snippet: &MachineSnippet) -> anyhow::Result<Machine> {
         match snippet {
             MachineSnippet::Source(snippet) => Ok(Machine::new_source(&snippet.item)),
@@ -120,10 +284,33 @@ impl Editor {
             machines.push(EditorMachine {
                 snippet: machine.clone(),
                 machine: self.create_machine(&machine)?,
+                exact_count: None,
             });
         }
         self.machines = machines;
         self.item_speed_constraints = snippet.item_speed_constraints;
+        self.input_caps = snippet.input_caps;
+        self.item_policies = snippet.item_policies;
+        self.integer_machine_counts = snippet.integer_machine_counts;
+        self.exact_machine_counts = snippet.exact_machine_counts;
+        self.planned_research = snippet.planned_research;
+        self.use_planned_research = snippet.use_planned_research;
+        self.update_research();
+        if let Err(err) = self.update_planet(snippet.planet) {
+            warn!("{err}");
+        }
+        self.transit_time = snippet.transit_time.map(f64::from);
+        self.max_latency = snippet.max_latency.map(f64::from);
+        self.rocket_shipments = snippet.rocket_shipments;
+        self.power_plants = snippet
+            .power_plants
+            .into_iter()
+            .map(|(source, power)| (source, power.into()))
+            .collect();
+        self.train_round_trip_time = snippet.train_round_trip_time.map(f64::from);
+        self.wagons_per_train = snippet.wagons_per_train;
+        self.cargo_wagon = snippet.cargo_wagon;
+        self.fluid_wagon = snippet.fluid_wagon;
         self.after_machines_changed();
         Ok(())
     }
@@ -136,6 +323,23 @@ impl Editor {
     pub fn clear(&mut self) {
         self.machines.clear();
         self.item_speed_constraints.clear();
+        self.input_caps.clear();
+        self.item_policies.clear();
+        self.integer_machine_counts = false;
+        self.exact_machine_counts = false;
+        self.planned_research.clear();
+        self.use_planned_research = false;
+        self.update_research();
+        self.planet = None;
+        self.info.planet = None;
+        self.transit_time = None;
+        self.max_latency = None;
+        self.rocket_shipments.clear();
+        self.power_plants.clear();
+        self.train_round_trip_time = None;
+        self.wagons_per_train = None;
+        self.cargo_wagon = None;
+        self.fluid_wagon = None;
         self.solved = true;
     }
 
@@ -146,7 +350,11 @@ impl Editor {
         self.solved = false;
         let snippet = MachineSnippet::Source(SourceSinkSnippet { item: item.clone() });
         let machine = self.create_machine(&snippet)?;
-        self.machines.push(EditorMachine { snippet, machine });
+        self.machines.push(EditorMachine {
+            snippet,
+            machine,
+            exact_count: None,
+        });
         Ok(())
     }
 
@@ -157,7 +365,11 @@ impl Editor {
         self.solved = false;
         let snippet = MachineSnippet::Sink(SourceSinkSnippet { item: item.clone() });
         let machine = self.create_machine(&snippet)?;
-        self.machines.push(EditorMachine { snippet, machine });
+        self.machines.push(EditorMachine {
+            snippet,
+            machine,
+            exact_count: None,
+        });
         Ok(())
     }
 
@@ -195,7 +407,11 @@ impl Editor {
         }
         .into();
         let machine = self.create_machine(&snippet)?;
-        self.machines.push(EditorMachine { snippet, machine });
+        self.machines.push(EditorMachine {
+            snippet,
+            machine,
+            exact_count: None,
+        });
 
         if add_auto_constraint {
             if let Some(product) = recipe.products.get(0) {
@@ -278,17 +494,58 @@ impl Editor {
             "Inputs: {}",
             inputs
                 .iter()
-                .map(|i| { format!("{} {}", i.speed, i.item) })
+                .map(|i| format!("{} {}", self.info.format_speed(&i.item, i.speed), i.item))
                 .join(" + ")
         )
         .unwrap();
+        if let Some(planet) = &self.planet {
+            writeln!(out, "Planet: {planet}").unwrap();
+        }
+        for machine in &self.machines {
+            if machine.machine.crafter.is_source() {
+                if let Some(problem) = self.planet_problem(&machine.machine) {
+                    writeln!(out, "Warning: {problem}").unwrap();
+                }
+            }
+        }
         writeln!(out, "==============================").unwrap();
 
         for machine in &self.machines {
             if machine.machine.crafter.is_source_or_sink() {
                 continue;
             }
-            writeln!(out, "{}", machine.machine.description()).unwrap();
+            writeln!(out, "{}", machine.machine.description(&self.info)).unwrap();
+            let research = machine.machine.research;
+            if research.productivity != 0.0 {
+                writeln!(
+                    out,
+                    "Research: +{}% productivity",
+                    rf(research.productivity * 100.0)
+                )
+                .unwrap();
+            }
+            if let Some(problem) = self.planet_problem(&machine.machine) {
+                writeln!(out, "Warning: {problem}").unwrap();
+            }
+            for limit in machine.machine.effect_limits() {
+                writeln!(out, "Warning: {}", limit.description()).unwrap();
+            }
+            if let Some(count) = &machine.exact_count {
+                writeln!(out, "Exact count: {}", format_rational(count)).unwrap();
+            }
+            let emissions = machine.machine.emissions();
+            if !emissions.is_empty() {
+                writeln!(out, "Emissions: {}", format_emissions(&emissions)).unwrap();
+            }
+            if self.integer_machine_counts {
+                writeln!(
+                    out,
+                    "Build {} ({}% utilization)",
+                    machine.buildings(),
+                    rf(machine.utilization() * 100.0)
+                )
+                .unwrap();
+            }
             let beacon_text = machine.machine.beacon_text();
             let modules_text = module_counts(&machine.machine.modules)
                 .into_iter()
@@ -313,10 +570,93 @@ impl Editor {
             "Outputs: {}",
             outputs
                 .iter()
-                .map(|i| { format!("{} {}", -i.speed, i.item) })
+                .map(|i| format!("{} {}", self.info.format_speed(&i.item, -i.speed), i.item))
                 .join(" + ")
         )
         .unwrap();
+        if !self.limiting_input_caps.is_empty() {
+            writeln!(
+                out,
+                "Limited by the input cap of {}",
+                self.limiting_input_caps.iter().join(", ")
+            )
+            .unwrap();
+        }
+        if let Some(spare) = self
+            .spare_capacity
+            .as_ref()
+            .filter(|s| !s.outputs.is_empty())
+        {
+            let speeds_text = |speeds: &BTreeMap<ItemName, Speed>| {
+                speeds
+                    .iter()
+                    .map(|(item, speed)| format!("{} {item}", self.info.format_speed(item, *speed)))
+                    .join(" + ")
+            };
+            write!(
+                out,
+                "Estimated spare capacity (×{}): {}",
+                rf(spare.scale),
+                speeds_text(&spare.outputs)
+            )
+            .unwrap();
+            if !spare.inputs.is_empty() {
+                write!(out, " using {}", speeds_text(&spare.inputs)).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        let power_usage = self.power_usage();
+        if power_usage > 0.0 {
+            writeln!(out, "Power usage: {}", format_power(power_usage)).unwrap();
+        }
+        let emissions = self.emissions();
+        if !emissions.is_empty() {
+            writeln!(out, "Total emissions: {}", format_emissions(&emissions)).unwrap();
+        }
+        for (source, power) in &self.power_plants {
+            match power::plan(&self.info, *source, *power) {
+                Ok(plan) => writeln!(
+                    out,
+                    "{} power ({}): {}",
+                    source.name(),
+                    format_power(*power),
+                    plan.buildings_text()
+                )
+                .unwrap(),
+                Err(err) => writeln!(out, "Warning: {err}").unwrap(),
+            }
+        }
+        if !self.rocket_shipments.is_empty() {
+            match rocket::plan(&self.info, &self.rocket_shipments) {
+                Ok(plan) => writeln!(
+                    out,
+                    "Rockets (estimate): {} per minute ({} {ROCKET_PART})",
+                    rf(plan.rockets_per_minute()),
+                    self.info
+                        .format_speed(&ROCKET_PART.into(), plan.rocket_parts)
+                )
+                .unwrap(),
+                Err(err) => writeln!(out, "Warning: {err}").unwrap(),
+            }
+        }
+        let spoilage = spoilage::analyze(self);
+        for item in &spoilage {
+            if let Some(warning) = &item.warning {
+                writeln!(out, "Warning: {warning}").unwrap();
+            }
+        }
+        let byproducts = spoilage::byproducts(&spoilage);
+        if !byproducts.is_empty() {
+            writeln!(
+                out,
+                "Spoiled in transit: {}",
+                byproducts
+                    .iter()
+                    .map(|(item, speed)| format!("{} {item}", self.info.format_speed(item, *speed)))
+                    .join(" + ")
+            )
+            .unwrap();
+        }
         out
     }
 
@@ -367,6 +707,80 @@ impl Editor {
         Ok(())
     }
 
+    /// Multiplies all item speed constraints, machine count constraints and input caps
+    /// by `factor`. Caps are scaled too, otherwise a binding cap would keep the solution as it is.
+    pub fn scale(&mut self, factor: f64) -> anyhow::Result<()> {
+        ensure!(
+            factor.is_finite() && factor > 0.0,
+            "scale factor must be positive"
+        );
+        for speed in self
+            .item_speed_constraints
+            .values_mut()
+            .chain(self.input_caps.values_mut())
+        {
+            *speed = *speed * factor;
+        }
+        for machine in &mut self.machines {
+            if let MachineSnippet::Crafter(snippet) = &mut machine.snippet {
+                if let Some(count) = &mut snippet.count_constraint {
+                    *count = (f64::from(*count) * factor).into();
+                }
+            }
+        }
+        self.solve();
+        Ok(())
+    }
+
+    /// Scales the snippet (see `Editor::scale`) by the largest factor that doesn't exceed the limit.
+    /// The solution is linear in constraints, so the factor is the ratio of the limit and
+    /// the current value. In integer mode a machine count limit applies to whole buildings,
+    /// so the scaled count is at most the limit rounded down. Returns the applied factor.
+    pub fn fit_to(&mut self, limit: &ScaleLimit) -> anyhow::Result<f64> {
+        ensure!(self.solved, "snippet must be solved before fitting");
+        let (current, limit) = match limit {
+            ScaleLimit::Input { item, speed } => {
+                let current: Speed = self
+                    .machines
+                    .iter()
+                    .filter(|m| m.machine.crafter.is_source())
+                    .flat_map(|m| m.machine.item_speeds())
+                    .filter(|i| &i.item == item)
+                    .map(|i| i.speed)
+                    .sum();
+                (f64::from(current), f64::from(*speed))
+            }
+            ScaleLimit::MachineCount {
+                machine_index,
+                count,
+            } => {
+                let machine = self
+                    .machines
+                    .get(*machine_index)
+                    .context("invalid machine index")?;
+                ensure!(
+                    !machine.machine.crafter.is_source_or_sink(),
+                    "machine count limit is not allowed for sources and sinks"
+                );
+                let mut count = f64::from(*count);
+                if self.integer_machine_counts {
+                    // ceil(current * factor) <= count if and only if current * factor <= floor(count).
+                    count = (count + BUILDING_COUNT_EPSILON).floor();
+                    ensure!(count > 0.0, "limit must be at least one building");
+                }
+                (machine.machine.crafter_count, count)
+            }
+        };
+        ensure!(
+            current > 0.0,
+            "limited value is zero in the current solution"
+        );
+        ensure!(limit > 0.0, "limit must be positive");
+        let factor = limit / current;
+        self.scale(factor)?;
+        Ok(factor)
+    }
+
     // Clear without solving
     fn clear_all_constraints_internal(&mut self) {
         self.item_speed_constraints.clear();
@@ -387,7 +801,7 @@ impl Editor {
             .context("invalid machine index")?;
         let module = self.info.module(module)?;
         match module.type_ {
-            ModuleType::Speed => {}
+            ModuleType::Speed | ModuleType::Efficiency => {}
             ModuleType::Productivity => {
                 if !machine.machine.recipe.allowed_effects.productivity {
                     bail!("machin recipe doesn't allow productivity");
@@ -445,20 +859,127 @@ impl Editor {
         machine_index: usize,
         new_beacons: Vec<Beacon>,
     ) -> anyhow::Result<()> {
+        self.update_beacons(machine_index, new_beacons)?;
+        self.solve();
+        Ok(())
+    }
+
+    /// Replaces modules and beacons of all machines listed in the optimization result.
+    /// Nothing is changed if any of the machines can't be updated.
+    pub fn apply_optimization(&mut self, optimization: &Optimization) -> anyhow::Result<()> {
+        for machine in &optimization.machines {
+            self.check_modules(machine.machine_index, &machine.modules)?;
+            self.check_beacons(machine.machine_index, &machine.beacons)?;
+        }
+        for machine in &optimization.machines {
+            self.update_modules(machine.machine_index, machine.modules.clone())?;
+            self.update_beacons(machine.machine_index, machine.beacons.clone())?;
+        }
+        self.solve();
+        Ok(())
+    }
+
+    /// Copy of the editor for trying configurations without changing the snippet.
+    /// Exact mode is turned off since trials only compare float counts.
+    pub fn trial(&self) -> Editor {
+        let mut editor = self.clone();
+        editor.exact_machine_counts = false;
+        editor
+    }
+
+    /// Replaces modules and beacons of a machine and solves the snippet again.
+    /// Unlike `apply_optimization`, solver errors are returned instead of being reported.
+    pub fn try_configuration(
+        &mut self,
+        machine_index: usize,
+        modules: Vec<Module>,
+        beacons: Vec<Beacon>,
+    ) -> anyhow::Result<()> {
+        self.update_modules(machine_index, modules)?;
+        self.update_beacons(machine_index, beacons)?;
+        self.apply_research();
+        self.try_solve()
+    }
+
+    fn check_modules(&self, machine_index: usize, new_modules: &[Module]) -> anyhow::Result<()> {
         let machine = self
             .machines
-            .get_mut(machine_index)
+            .get(machine_index)
             .context("invalid machine index")?;
-        if new_beacons.iter().any(|b| b.modules.len() > 2) {
-            bail!("too many modules in a beacon");
+        if machine.machine.crafter.is_source_or_sink() {
+            bail!("modules are not supported for source and sink");
         }
-        if new_beacons
+        if new_modules.len() as u64 > machine.machine.crafter.module_inventory_size {
+            bail!("too many modules in {}", machine.machine.crafter.name);
+        }
+        if let Some(module) = new_modules
             .iter()
-            .flat_map(|b| &b.modules)
-            .any(|m| m.type_ == ModuleType::Productivity)
+            .find(|m| !m.is_allowed_by(&machine.machine.recipe.allowed_effects))
         {
-            bail!("productivity modules are not allowed in beacons");
+            bail!(
+                "{} is not allowed in {}",
+                module.name,
+                machine.machine.recipe.name
+            );
         }
+        Ok(())
+    }
+
+    fn check_beacons(&self, machine_index: usize, new_beacons: &[Beacon]) -> anyhow::Result<()> {
+        let machine = self
+            .machines
+            .get(machine_index)
+            .context("invalid machine index")?;
+        if machine.machine.crafter.is_source_or_sink() {
+            bail!("beacons are not supported for source and sink");
+        }
+        for beacon in new_beacons {
+            if beacon.modules.len() as u64 > beacon.type_.module_inventory_size {
+                bail!("too many modules in {}", beacon.type_.name);
+            }
+            if let Some(module) = beacon
+                .modules
+                .iter()
+                .find(|m| !m.is_allowed_by(&beacon.type_.allowed_effects))
+            {
+                bail!("{} is not allowed in {}", module.name, beacon.type_.name);
+            }
+        }
+        Ok(())
+    }
+
+    fn update_modules(
+        &mut self,
+        machine_index: usize,
+        new_modules: Vec<Module>,
+    ) -> anyhow::Result<()> {
+        self.check_modules(machine_index, &new_modules)?;
+        let machine = self
+            .machines
+            .get_mut(machine_index)
+            .context("invalid machine index")?;
+        match &mut machine.snippet {
+            MachineSnippet::Source { .. } | MachineSnippet::Sink { .. } => {
+                bail!("modules are not supported for source and sink")
+            }
+            MachineSnippet::Crafter(snippet) => {
+                snippet.modules = new_modules.iter().map(|m| m.name.clone()).collect_vec();
+                machine.machine.modules = new_modules;
+            }
+        }
+        Ok(())
+    }
+
+    fn update_beacons(
+        &mut self,
+        machine_index: usize,
+        new_beacons: Vec<Beacon>,
+    ) -> anyhow::Result<()> {
+        self.check_beacons(machine_index, &new_beacons)?;
+        let machine = self
+            .machines
+            .get_mut(machine_index)
+            .context("invalid machine index")?;
         match &mut machine.snippet {
             MachineSnippet::Source { .. } | MachineSnippet::Sink { .. } => {
                 bail!("beacons are not supported for source and sink")
@@ -467,13 +988,15 @@ impl Editor {
                 snippet.beacons = new_beacons
                     .iter()
                     .map(|beacon| BeaconSnippet {
+                        beacon: Some(beacon.type_.name.clone())
+                            .filter(|name| name != &BeaconName::default_name()),
+                        quality: beacon.quality,
                         modules: beacon.modules.iter().map(|m| m.name.clone()).collect_vec(),
                     })
                     .collect_vec();
                 machine.machine.beacons = new_beacons;
             }
         }
-        self.solve();
         Ok(())
     }
 
@@ -492,6 +1015,7 @@ impl Editor {
     }
 
     fn solve(&mut self) {
+        self.apply_research();
         if let Err(err) = self.try_solve() {
             warn!("failed to solve: {err}");
         }
@@ -509,17 +1033,52 @@ impl Editor {
             ItemSumsToZero { item: ItemName },
             ItemProduction { item: ItemName, speed: Speed },
             MachineCount { index: usize, count: MachineCount },
+            // An upper bound of the consumption of an input, unlike other constraints.
+            InputCap { item: ItemName, cap: Speed },
         }
 
         self.solved = false;
+        self.limiting_input_caps.clear();
+        self.spare_capacity = None;
+        self.sensitivity = Sensitivity::default();
         if self.machines.is_empty() {
             self.solved = true;
             return Ok(());
         }
         for machine in &mut self.machines {
             machine.machine.crafter_count = 1.0;
+            machine.exact_count = None;
         }
         let items = self.added_items();
+        let source_speed = |machine: &EditorMachine, item: &ItemName| -> f64 {
+            if !machine.machine.crafter.is_source() {
+                return 0.0;
+            }
+            machine
+                .machine
+                .item_speeds()
+                .filter(|i| &i.item == item)
+                .map(|i| i.speed)
+                .sum::<Speed>()
+                .into()
+        };
+        let input_caps = self
+            .input_caps
+            .iter()
+            .filter(|(item, _)| {
+                let is_input = self.machines.iter().any(|m| source_speed(m, item) > 0.0);
+                if !is_input {
+                    warn!(
+                        "input cap for {item} is ignored because it's not an input of the snippet"
+                    );
+                }
+                is_input
+            })
+            .map(|(item, cap)| Constraint::InputCap {
+                item: item.clone(),
+                cap: *cap,
+            })
+            .collect_vec();
         let constraints: Vec<_> = items
             .iter()
             .map(|item| Constraint::ItemSumsToZero { item: item.clone() })
@@ -540,6 +1099,7 @@ impl Editor {
                             .map(|count| Constraint::MachineCount { index, count }),
                     }),
             )
+            .chain(input_caps)
             .collect();
 
         let a = DMatrix::from_fn(constraints.len(), self.machines.len(), |row, col| {
@@ -547,7 +1107,7 @@ impl Editor {
             match &constraints[row] {
                 Constraint::ItemSumsToZero { item } => machine
                     .machine
-                    .item_speeds()
+                    .net_item_speeds()
                     .into_iter()
                     .filter(|i| &i.item == item)
                     .map(|i| i.speed)
@@ -555,7 +1115,7 @@ impl Editor {
                     .into(),
                 Constraint::ItemProduction { item, speed: _ } => machine
                     .machine
-                    .item_speeds()
+                    .net_item_speeds()
                     .into_iter()
                     .filter(|i| &i.item == item && i.speed > Speed::ZERO)
                     .map(|i| i.speed)
@@ -571,12 +1131,14 @@ impl Editor {
                         0.0
                     }
                 }
+                Constraint::InputCap { item, cap: _ } => source_speed(machine, item),
             }
         });
         let b = DVector::from_fn(constraints.len(), |row, _| match &constraints[row] {
             Constraint::ItemSumsToZero { item: _ } => 0.0,
             Constraint::ItemProduction { item: _, speed } => (*speed).into(),
             Constraint::MachineCount { index: _, count } => (*count).into(),
+            Constraint::InputCap { item: _, cap } => (*cap).into(),
         });
         trace!("constraints: {constraints:?}");
         trace!("a=");
@@ -585,11 +1147,48 @@ impl Editor {
         }
         trace!("b={b:?}");
 
-        let svd = a.clone().svd(true, true);
-        let output = svd
-            .solve(&b, 0.000001)
-            .map_err(|str| format_err!("{str}"))?;
-        trace!("output {output:?}");
+        let rows_where = |condition: fn(&Constraint) -> bool| {
+            (0..constraints.len())
+                .filter(|row| condition(&constraints[*row]))
+                .collect_vec()
+        };
+        let equation_rows = rows_where(|c| !matches!(c, Constraint::InputCap { .. }));
+        let pinned_rows = rows_where(|c| {
+            matches!(
+                c,
+                Constraint::ItemProduction { .. } | Constraint::MachineCount { .. }
+            )
+        });
+        let cap_rows = rows_where(|c| matches!(c, Constraint::InputCap { .. }));
+
+        let mut output = if cap_rows.is_empty() || !pinned_rows.is_empty() {
+            let a_equations = a.select_rows(&equation_rows);
+            let b_equations = b.select_rows(&equation_rows);
+            let svd = a_equations.clone().svd(true, true);
+            let mut output = svd
+                .solve(&b_equations, 0.000001)
+                .map_err(|str| format_err!("{str}"))?;
+            trace!("output {output:?}");
+            if output.iter().any(|x| *x < 0.0) {
+                // Happens with cycles and byproducts when the system has more than one solution.
+                output = non_negative_least_squares(&a_equations, &b_equations);
+                trace!("non-negative output {output:?}");
+            }
+            output
+        } else {
+            // Only input caps set the scale.
+            DVector::zeros(self.machines.len())
+        };
+        let usage = &a * &output;
+        let capped = !cap_rows.is_empty()
+            && (pinned_rows.is_empty()
+                || cap_rows
+                    .iter()
+                    .any(|row| usage[*row] > b[*row] * (1.0 + 1e-9)));
+        if capped {
+            output = solve_with_upper_bounds(&a, &b, &pinned_rows, &cap_rows);
+            trace!("capped output {output:?}");
+        }
 
         if output.iter().all(|v| *v == 0.0) {
             bail!("solve result is zero; try adding more constraints");
@@ -599,7 +1198,21 @@ impl Editor {
             machine.machine.crafter_count = *output_item;
         }
 
-        let error = (a * output.clone() - b).norm();
+        let residual = &a * &output - &b;
+        // Input caps may override other constraints, but never the balance of items.
+        let error = constraints
+            .iter()
+            .zip(residual.iter())
+            .map(|(constraint, value)| match constraint {
+                Constraint::InputCap { .. } => value.max(0.0),
+                Constraint::ItemProduction { .. } | Constraint::MachineCount { .. } if capped => {
+                    0.0
+                }
+                _ => *value,
+            })
+            .map(|value| value * value)
+            .sum::<f64>()
+            .sqrt();
         if error > 0.01 {
             bail!("couldn't fit all constraints (error = {}); try removing constraints or changing their values", rf(error));
         }
@@ -607,65 +1220,297 @@ impl Editor {
             bail!("solution is negative! try adding more constraints");
         }
 
+        // Rows that hold as equations in the solution.
+        let active_rows = if capped {
+            (0..constraints.len())
+                .filter(|row| {
+                    let tolerance = 1e-6 * b[*row].abs().max(1.0);
+                    match &constraints[*row] {
+                        Constraint::ItemSumsToZero { .. } => true,
+                        Constraint::InputCap { .. } => residual[*row] > -tolerance,
+                        _ => residual[*row].abs() <= tolerance,
+                    }
+                })
+                .collect_vec()
+        } else {
+            equation_rows
+        };
+        if capped {
+            let overridden = pinned_rows
+                .iter()
+                .filter(|row| !active_rows.contains(row))
+                .filter_map(|row| match &constraints[*row] {
+                    Constraint::ItemProduction { item, .. } => {
+                        Some(format!("production of {item}"))
+                    }
+                    Constraint::MachineCount { index, .. } => Some(format!(
+                        "count of {}",
+                        self.machines[*index].machine.recipe.name
+                    )),
+                    _ => None,
+                })
+                .collect_vec();
+            if !overridden.is_empty() {
+                warn!("input caps override constraints: {}", overridden.join(", "));
+            }
+            self.limiting_input_caps = active_rows
+                .iter()
+                .filter_map(|row| match &constraints[*row] {
+                    Constraint::InputCap { item, .. } => Some(item.clone()),
+                    _ => None,
+                })
+                .collect();
+        }
+        let a_active = a.select_rows(&active_rows);
+        let b_active = b.select_rows(&active_rows);
+        if self.exact_machine_counts {
+            if self.has_beacons() {
+                warn!("exact machine counts are unavailable with beacons");
+            } else {
+                self.apply_exact_solution(&a_active, &b_active);
+            }
+        }
+        if self.integer_machine_counts {
+            self.spare_capacity = Some(self.solve_spare_capacity());
+        }
+
+        let targets = active_rows
+            .iter()
+            .enumerate()
+            .filter_map(|(active_row, row)| match &constraints[*row] {
+                Constraint::ItemSumsToZero { .. } => None,
+                Constraint::ItemProduction { item, .. } => {
+                    Some((active_row, Target::ItemProduction(item.clone())))
+                }
+                Constraint::MachineCount { index, .. } => Some((
+                    active_row,
+                    Target::MachineCount {
+                        machine_index: *index,
+                    },
+                )),
+                Constraint::InputCap { item, .. } => {
+                    Some((active_row, Target::InputCap(item.clone())))
+                }
+            })
+            .collect_vec();
+        let counts = DVector::from_iterator(
+            self.machines.len(),
+            self.machines.iter().map(|m| m.machine.crafter_count),
+        );
+        self.sensitivity = sensitivity::compute(&a_active, &b_active, &targets, &counts);
+
         self.solved = true;
         Ok(())
     }
 
+    /// Repeats the solve with fractions instead of floats. Matrix entries are recovered
+    /// from floats (see `rational::to_rational`). Keeps the float solution if the system
+    /// doesn't have exactly one solution, e.g. if it was solved with least squares.
+    ///
+    /// Beacon transmission falls off as 1/sqrt(n), so rates of machines with beacons are
+    /// usually irrational and would be recovered as long fractions that only look exact.
+    /// The solve is skipped for such snippets.
+    fn apply_exact_solution(&mut self, a: &DMatrix<f64>, b: &DVector<f64>) {
+        let rows = a
+            .row_iter()
+            .map(|row| row.iter().map(|value| to_rational(*value)).collect())
+            .collect::<Option<Vec<Vec<_>>>>();
+        let values = b
+            .iter()
+            .map(|value| to_rational(*value))
+            .collect::<Option<Vec<_>>>();
+        let (Some(rows), Some(values)) = (rows, values) else {
+            warn!("exact machine counts are unavailable: some rates are not simple fractions");
+            return;
+        };
+        let Some(counts) = solve_exact(&rows, &values) else {
+            warn!(
+                "exact machine counts are unavailable: constraints don't define a single solution"
+            );
+            return;
+        };
+        if counts.iter().any(|count| count.is_negative()) {
+            warn!("exact machine counts are unavailable: exact solution is negative");
+            return;
+        }
+        for (machine, count) in self.machines.iter_mut().zip_eq(counts) {
+            machine.machine.crafter_count = count.to_f64().unwrap_or(f64::NAN);
+            machine.exact_count = Some(count);
+        }
+    }
+
+    /// Estimates the spare capacity by scaling the current solution by the largest factor
+    /// that keeps every crafter row within its whole buildings and every input within its cap.
+    /// The snippet is not solved again: a bounded solve could shift rates between rows
+    /// (e.g. with several recipes for an item) and find more capacity than the estimate.
+    fn solve_spare_capacity(&self) -> SpareCapacity {
+        let mut scale = f64::INFINITY;
+        for machine in &self.machines {
+            if machine.machine.crafter.is_source_or_sink()
+                || machine.machine.crafter_count <= BUILDING_COUNT_EPSILON
+            {
+                continue;
+            }
+            scale = scale.min(machine.buildings() as f64 / machine.machine.crafter_count);
+        }
+        for (item, cap) in &self.input_caps {
+            let usage: Speed = self
+                .machines
+                .iter()
+                .filter(|m| m.machine.crafter.is_source())
+                .flat_map(|m| m.machine.item_speeds())
+                .filter(|i| &i.item == item)
+                .map(|i| i.speed)
+                .sum();
+            if usage > Speed::ZERO {
+                scale = scale.min(*cap / usage);
+            }
+        }
+        if !scale.is_finite() || scale < 1.0 {
+            scale = 1.0;
+        }
+        let mut spare = SpareCapacity {
+            scale,
+            outputs: BTreeMap::new(),
+            inputs: BTreeMap::new(),
+        };
+        if scale - 1.0 <= BUILDING_COUNT_EPSILON {
+            return spare;
+        }
+        for machine in &self.machines {
+            // Sources produce inputs of the snippet, sinks consume its outputs.
+            let (target, sign) = if machine.machine.crafter.is_source() {
+                (&mut spare.inputs, 1.0)
+            } else if machine.machine.crafter.is_sink() {
+                (&mut spare.outputs, -1.0)
+            } else {
+                continue;
+            };
+            for item_speed in machine
+                .machine
+                .item_speeds()
+                .filter(|i| i.speed != Speed::ZERO)
+            {
+                *target.entry(item_speed.item).or_insert(Speed::ZERO) +=
+                    item_speed.speed * (sign * (scale - 1.0));
+            }
+        }
+        spare
+    }
+
     fn add_sources_and_sinks(&mut self) -> anyhow::Result<()> {
         self.machines
             .retain(|m| !m.machine.crafter.is_source_or_sink());
         let items = self.added_items();
+        // Catalysts (e.g. uranium-235 in Kovarex enrichment) only count in the direction
+        // of their net speed.
+        let speeds = self
+            .machines
+            .iter()
+            .map(|m| m.machine.net_item_speeds_per_crafter())
+            .collect_vec();
         for item in items {
-            let any_inputs = self
-                .machines
+            let any_inputs = speeds
                 .iter()
-                .any(|m| m.machine.recipe.ingredients.iter().any(|i| i.name == item));
-            let any_outputs = self
-                .machines
+                .flatten()
+                .any(|i| i.item == item && i.speed < Speed::ZERO);
+            let any_outputs = speeds
                 .iter()
-                .any(|m| m.machine.recipe.products.iter().any(|i| i.name == item));
-            if any_inputs && !any_outputs {
+                .flatten()
+                .any(|i| i.item == item && i.speed > Speed::ZERO);
+            let (add_source, add_sink) = match self.item_policies.get(&item) {
+                None => (any_inputs && !any_outputs, !any_inputs && any_outputs),
+                Some(ItemPolicy::Balance) => (false, false),
+                Some(ItemPolicy::AllowSurplus) => (false, true),
+                Some(ItemPolicy::AllowDeficit) => (true, false),
+                Some(ItemPolicy::Void) => (false, self.void_recipe(&item).is_none()),
+            };
+            if add_source {
                 self.add_source(&item)?;
-            } else if !any_inputs && any_outputs {
+            }
+            if add_sink {
                 self.add_sink(&item)?;
             }
         }
         Ok(())
     }
 
+    /// Sorts machines so that producers go before consumers. Machines that form a cycle
+    /// (e.g. coal liquefaction consuming its own heavy oil) are kept together
+    /// in their original order.
     fn auto_sort_machines(&mut self) {
-        let mut new_machines = Vec::new();
-        let mut remaining_machines = self.machines.clone();
-        let mut crafted_items = BTreeSet::new();
-        loop {
-            let mut new_remaining_machines = Vec::new();
-            let old_count = new_machines.len();
-            for machine in remaining_machines {
-                if machine
-                    .machine
-                    .recipe
-                    .ingredients
-                    .iter()
-                    .all(|ing| crafted_items.contains(&ing.name))
-                {
-                    for product in &machine.machine.recipe.products {
-                        crafted_items.insert(product.name.clone());
-                    }
-                    new_machines.push(machine);
-                } else {
-                    new_remaining_machines.push(machine);
-                }
+        let speeds = self
+            .machines
+            .iter()
+            .map(|m| m.machine.net_item_speeds_per_crafter())
+            .collect_vec();
+        let edges = (0..self.machines.len())
+            .map(|from| {
+                (0..self.machines.len())
+                    .filter(|&to| {
+                        to != from
+                            && speeds[from].iter().any(|output| {
+                                output.speed > Speed::ZERO
+                                    && speeds[to].iter().any(|input| {
+                                        input.item == output.item && input.speed < Speed::ZERO
+                                    })
+                            })
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        let components = strongly_connected_components(&edges);
+        let mut component_of = vec![0; self.machines.len()];
+        for (index, component) in components.iter().enumerate() {
+            if component.len() > 1 {
+                trace!(
+                    "cycle: {}",
+                    component
+                        .iter()
+                        .map(|i| &self.machines[*i].machine.recipe.name)
+                        .join(", ")
+                );
             }
-            remaining_machines = new_remaining_machines;
-            if new_machines.len() == old_count {
-                break;
+            for machine in component {
+                component_of[*machine] = index;
             }
         }
-        if !remaining_machines.is_empty() {
-            warn!("remaining_machines is not empty: {remaining_machines:?}");
-            new_machines.extend(remaining_machines);
+
+        // Topological sort of components, preferring the original order.
+        let mut in_degree = vec![0; components.len()];
+        for (from, tos) in edges.iter().enumerate() {
+            for to in tos {
+                if component_of[from] != component_of[*to] {
+                    in_degree[component_of[*to]] += 1;
+                }
+            }
         }
-        self.machines = new_machines;
+        let mut order = Vec::new();
+        let mut ready: BTreeSet<(usize, usize)> = components
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| in_degree[*index] == 0)
+            .map(|(index, component)| (component[0], index))
+            .collect();
+        while let Some((_, index)) = ready.pop_first() {
+            for from in &components[index] {
+                for to in &edges[*from] {
+                    let to_component = component_of[*to];
+                    if to_component != index {
+                        in_degree[to_component] -= 1;
+                        if in_degree[to_component] == 0 {
+                            ready.insert((components[to_component][0], to_component));
+                        }
+                    }
+                }
+            }
+            order.extend(components[index].iter().copied());
+        }
+        let mut machines = self.machines.drain(..).map(Some).collect_vec();
+        self.machines = order
+            .into_iter()
+            .filter_map(|index| machines[index].take())
+            .collect();
     }
 
     fn after_machines_changed(&mut self) {
@@ -692,10 +1537,578 @@ impl Editor {
         Snippet {
             machines: self.machines.iter().map(|m| m.snippet.clone()).collect(),
             item_speed_constraints: self.item_speed_constraints.clone(),
+            input_caps: self.input_caps.clone(),
+            item_policies: self.item_policies.clone(),
+            integer_machine_counts: self.integer_machine_counts,
+            exact_machine_counts: self.exact_machine_counts,
+            planned_research: self.planned_research.clone(),
+            use_planned_research: self.use_planned_research,
+            planet: self.planet.clone(),
+            transit_time: self.transit_time.map(OrderedFloat),
+            max_latency: self.max_latency.map(OrderedFloat),
+            rocket_shipments: self.rocket_shipments.clone(),
+            power_plants: self
+                .power_plants
+                .iter()
+                .map(|(source, power)| (*source, OrderedFloat(*power)))
+                .collect(),
+            train_round_trip_time: self.train_round_trip_time.map(OrderedFloat),
+            wagons_per_train: self.wagons_per_train,
+            cargo_wagon: self.cargo_wagon.clone(),
+            fluid_wagon: self.fluid_wagon.clone(),
         }
     }
 
     pub fn item_speed_constraints(&self) -> &BTreeMap<ItemName, Speed> {
         &self.item_speed_constraints
     }
+
+    /// Sensitivity of the last successful solve.
+    pub fn sensitivity(&self) -> &Sensitivity {
+        &self.sensitivity
+    }
+
+    pub fn input_caps(&self) -> &BTreeMap<ItemName, Speed> {
+        &self.input_caps
+    }
+
+    /// Sets or removes the maximum consumption of an input. Caps reduce the solution if other
+    /// constraints need more of the input, overriding these constraints. If there are no other
+    /// constraints, the solution uses as much of the inputs as the caps allow.
+    pub fn set_input_cap(&mut self, item: &ItemName, cap: Option<Speed>) -> anyhow::Result<()> {
+        if !self.info.all_items.contains(item) {
+            bail!("unknown item: {item:?}");
+        }
+        if let Some(cap) = cap {
+            ensure!(cap > Speed::ZERO, "input cap must be positive");
+            self.input_caps.insert(item.clone(), cap);
+        } else {
+            self.input_caps.remove(item);
+        }
+        self.solve();
+        Ok(())
+    }
+
+    pub fn item_policies(&self) -> &BTreeMap<ItemName, ItemPolicy> {
+        &self.item_policies
+    }
+
+    /// Sets or removes the policy of an item. For `ItemPolicy::Void`, a crafter with
+    /// the void recipe is added if the game has such recipe and the snippet doesn't have it yet.
+    /// The crafter is removed again when the item gets a different policy or none.
+    pub fn set_item_policy(
+        &mut self,
+        item: &ItemName,
+        policy: Option<ItemPolicy>,
+    ) -> anyhow::Result<()> {
+        if !self.info.all_items.contains(item) {
+            bail!("unknown item: {item:?}");
+        }
+        let previous = if let Some(policy) = policy {
+            self.item_policies.insert(item.clone(), policy)
+        } else {
+            self.item_policies.remove(item)
+        };
+        if previous == Some(ItemPolicy::Void) && policy != Some(ItemPolicy::Void) {
+            // Remove the crafter that was added for the Void policy.
+            if let Some(recipe) = self.void_recipe(item).map(|r| r.name.clone()) {
+                self.machines.retain(|m| m.machine.recipe.name != recipe);
+            }
+        }
+        if policy == Some(ItemPolicy::Void) {
+            if let Some(recipe) = self.void_recipe(item).map(|r| r.name.clone()) {
+                if !self
+                    .machines
+                    .iter()
+                    .any(|m| m.machine.recipe.name == recipe)
+                {
+                    return self.add_crafter(&recipe, None);
+                }
+            }
+        }
+        self.after_machines_changed();
+        Ok(())
+    }
+
+    /// Recipe that consumes the item and produces nothing.
+    fn void_recipe(&self, item: &ItemName) -> Option<&Recipe> {
+        self.info.game_data.recipes.values().find(|recipe| {
+            recipe.products.is_empty()
+                && recipe.ingredients.len() == 1
+                && &recipe.ingredients[0].name == item
+        })
+    }
+
+    /// Changes the rate unit and saves it to the config file.
+    pub fn set_rate_unit(&mut self, unit: RateUnit) -> anyhow::Result<()> {
+        self.info.config.rate_unit = unit;
+        config::save_value("rate_unit", &self.info.config.rate_unit)
+    }
+
+    pub fn set_recipe_filter(&mut self, filter: RecipeFilter) {
+        self.info.config.recipe_filter = filter;
+    }
+
+    pub fn integer_machine_counts(&self) -> bool {
+        self.integer_machine_counts
+    }
+
+    /// In integer mode every crafter count is rounded up to whole buildings, and the solve also
+    /// estimates the rates these buildings could reach at full speed (see `Editor::spare_capacity`).
+    /// The solved counts stay as they are, so the constraints are still met exactly.
+    pub fn set_integer_machine_counts(&mut self, value: bool) {
+        self.integer_machine_counts = value;
+        self.solve();
+    }
+
+    fn update_research(&mut self) {
+        let levels = if self.use_planned_research {
+            combined_levels(&self.info.config.research, &self.planned_research)
+        } else {
+            self.info.config.research.clone()
+        };
+        self.research_bonuses = ResearchBonuses::new(&self.info.game_data, &levels);
+        self.info.update_unlocked_recipes(&levels);
+    }
+
+    // Crafters can be changed after creation, so bonuses are updated before each solve.
+    fn apply_research(&mut self) {
+        for machine in &mut self.machines {
+            if machine.machine.crafter.is_source_or_sink() {
+                continue;
+            }
+            machine.machine.research = self.research_bonuses.machine_bonus(
+                &self.info.game_data,
+                &machine.machine.crafter.name,
+                &machine.machine.recipe.name,
+            );
+        }
+    }
+
+    /// Research levels that are planned in addition to the current research from the config.
+    pub fn planned_research(&self) -> &ResearchLevels {
+        &self.planned_research
+    }
+
+    pub fn set_planned_research(
+        &mut self,
+        technology: &TechnologyName,
+        level: Option<u32>,
+    ) -> anyhow::Result<()> {
+        let data = self
+            .info
+            .game_data
+            .technologies
+            .get(technology)
+            .with_context(|| format!("unknown technology: {technology:?}"))?;
+        if let Some(level) = level {
+            let max_level = data.max_level.saturating_sub(data.level).saturating_add(1);
+            ensure!(
+                (1..=max_level).contains(&level),
+                "level of {technology} must be from 1 to {max_level}"
+            );
+            self.planned_research.insert(technology.clone(), level);
+        } else {
+            self.planned_research.remove(technology);
+        }
+        self.update_research();
+        self.solve();
+        Ok(())
+    }
+
+    pub fn use_planned_research(&self) -> bool {
+        self.use_planned_research
+    }
+
+    /// Switches between the current research and the research after the planned technologies,
+    /// to compare the two.
+    pub fn set_use_planned_research(&mut self, value: bool) {
+        self.use_planned_research = value;
+        self.update_research();
+        self.solve();
+    }
+
+    pub fn planet(&self) -> Option<&PlanetName> {
+        self.planet.as_ref()
+    }
+
+    /// Selects the planet of the snippet. Menus and automatic crafter selection
+    /// only offer recipes and crafters that meet its surface conditions.
+    pub fn set_planet(&mut self, planet: Option<PlanetName>) -> anyhow::Result<()> {
+        self.update_planet(planet)?;
+        for machine in &self.machines {
+            if let Some(problem) = self.planet_problem(&machine.machine) {
+                warn!("{problem}");
+            }
+        }
+        self.solve();
+        Ok(())
+    }
+
+    fn update_planet(&mut self, planet: Option<PlanetName>) -> anyhow::Result<()> {
+        self.info.planet = planet
+            .as_ref()
+            .map(|name| {
+                self.info
+                    .game_data
+                    .planets
+                    .get(name)
+                    .cloned()
+                    .with_context(|| format!("unknown planet: {name:?}"))
+            })
+            .transpose()?;
+        self.planet = planet;
+        Ok(())
+    }
+
+    /// Why the machine can't work on the planet of the snippet.
+    pub fn planet_problem(&self, machine: &Machine) -> Option<String> {
+        let planet = self.planet.as_ref()?;
+        if machine.crafter.is_source() {
+            let item = &machine.recipe.products[0].name;
+            self.info
+                .is_resource_missing_on_planet(item)
+                .then(|| format!("{item} is not a resource on {planet}"))
+        } else if machine.crafter.is_sink() {
+            None
+        } else if !self.info.is_recipe_possible(&machine.recipe) {
+            Some(format!(
+                "recipe {} can't be used on {planet}",
+                machine.recipe.name
+            ))
+        } else if !self.info.is_crafter_possible(&machine.crafter.name) {
+            Some(format!("{} can't work on {planet}", machine.crafter.name))
+        } else {
+            None
+        }
+    }
+
+    /// Average time items spend between machines, in seconds.
+    pub fn transit_time(&self) -> f64 {
+        self.transit_time.unwrap_or(DEFAULT_TRANSIT_TIME)
+    }
+
+    pub fn max_latency(&self) -> Option<f64> {
+        self.max_latency
+    }
+
+    /// Sets the parameters of spoilage estimates. `None` restores the default transit time
+    /// or disables the latency warning.
+    pub fn set_spoilage_settings(
+        &mut self,
+        transit_time: Option<f64>,
+        max_latency: Option<f64>,
+    ) -> anyhow::Result<()> {
+        for value in transit_time.iter().chain(&max_latency) {
+            ensure!(value.is_finite() && *value >= 0.0, "invalid time: {value}");
+        }
+        self.transit_time = transit_time;
+        self.max_latency = max_latency;
+        Ok(())
+    }
+
+    pub fn rocket_shipments(&self) -> &BTreeMap<ItemName, Speed> {
+        &self.rocket_shipments
+    }
+
+    /// Sets or removes the rate of an item launched by rockets.
+    pub fn set_rocket_shipment(
+        &mut self,
+        item: &ItemName,
+        speed: Option<Speed>,
+    ) -> anyhow::Result<()> {
+        if !self.info.all_items.contains(item) {
+            bail!("unknown item: {item:?}");
+        }
+        if let Some(speed) = speed {
+            ensure!(speed > Speed::ZERO, "shipped rate must be positive");
+            self.rocket_shipments.insert(item.clone(), speed);
+        } else {
+            self.rocket_shipments.remove(item);
+        }
+        Ok(())
+    }
+
+    /// Power consumed by all machines, in watts.
+    pub fn power_usage(&self) -> f64 {
+        self.machines.iter().map(|m| m.machine.power_usage()).sum()
+    }
+
+    /// Emissions of all machines by pollutant, per minute.
+    pub fn emissions(&self) -> BTreeMap<String, f64> {
+        let mut emissions = BTreeMap::<String, f64>::new();
+        for machine in &self.machines {
+            for (pollutant, value) in machine.machine.emissions() {
+                *emissions.entry(pollutant).or_default() += value;
+            }
+        }
+        emissions
+    }
+
+    /// Power plants of the snippet, with the power they must generate in watts.
+    pub fn power_plants(&self) -> &BTreeMap<PowerSource, f64> {
+        &self.power_plants
+    }
+
+    /// Buildings of all power plants of the snippet. Power plants are not machines of the solver,
+    /// they take water from their own offshore pumps.
+    pub fn power_plant_buildings(&self) -> Vec<(ItemName, u64)> {
+        self.power_plants
+            .iter()
+            .filter_map(|(source, power)| power::plan(&self.info, *source, *power).ok())
+            .flat_map(|plan| plan.buildings)
+            .collect()
+    }
+
+    /// Adds a power plant to the snippet, or removes it. Its buildings are added
+    /// to the bill of materials.
+    pub fn set_power_plant(
+        &mut self,
+        source: PowerSource,
+        power: Option<f64>,
+    ) -> anyhow::Result<()> {
+        if let Some(power) = power {
+            // Check that the game data has all required buildings.
+            power::plan(&self.info, source, power)?;
+            self.power_plants.insert(source, power);
+        } else {
+            self.power_plants.remove(&source);
+        }
+        Ok(())
+    }
+
+    /// Round trip time of trains that serve sources and sinks, in seconds.
+    pub fn train_round_trip_time(&self) -> f64 {
+        self.train_round_trip_time
+            .unwrap_or(DEFAULT_ROUND_TRIP_TIME)
+    }
+
+    pub fn wagons_per_train(&self) -> u32 {
+        self.wagons_per_train.unwrap_or(DEFAULT_WAGONS_PER_TRAIN)
+    }
+
+    /// Wagon of train estimates with the entity type (e.g. "cargo-wagon"), if it was chosen.
+    pub fn wagon_of_type(&self, type_: &str) -> Option<&ItemName> {
+        match type_ {
+            "cargo-wagon" => self.cargo_wagon.as_ref(),
+            "fluid-wagon" => self.fluid_wagon.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Chooses the cargo wagon or the fluid wagon of train estimates, depending on its type.
+    pub fn set_wagon(&mut self, wagon: &ItemName) -> anyhow::Result<()> {
+        let type_ = self
+            .info
+            .game_data
+            .entities
+            .get(wagon)
+            .map(|entity| entity.type_.as_str())
+            .with_context(|| format!("unknown wagon: {wagon}"))?;
+        match type_ {
+            "cargo-wagon" => self.cargo_wagon = Some(wagon.clone()),
+            "fluid-wagon" => self.fluid_wagon = Some(wagon.clone()),
+            _ => bail!("{wagon} is not a wagon"),
+        }
+        Ok(())
+    }
+
+    /// Sets the parameters of train estimates. `None` restores the default value.
+    pub fn set_train_settings(
+        &mut self,
+        round_trip_time: Option<f64>,
+        wagons_per_train: Option<u32>,
+    ) -> anyhow::Result<()> {
+        if let Some(time) = round_trip_time {
+            ensure!(
+                time.is_finite() && time > 0.0,
+                "round trip time must be positive"
+            );
+        }
+        ensure!(
+            wagons_per_train != Some(0),
+            "a train must have at least one wagon"
+        );
+        self.train_round_trip_time = round_trip_time;
+        self.wagons_per_train = wagons_per_train;
+        Ok(())
+    }
+
+    /// Exact machine counts are unavailable if any machine has beacons.
+    pub fn has_beacons(&self) -> bool {
+        self.machines.iter().any(|m| !m.machine.beacons.is_empty())
+    }
+
+    pub fn exact_machine_counts(&self) -> bool {
+        self.exact_machine_counts
+    }
+
+    /// In exact mode the solve is repeated with fractions, so crafter counts like 37/3
+    /// don't become 12.333333. Only the counts are exact, rates are still floats.
+    /// Snippets with beacons are not supported (see `Editor::has_beacons`).
+    pub fn set_exact_machine_counts(&mut self, value: bool) {
+        self.exact_machine_counts = value;
+        self.solve();
+    }
+
+    /// Estimated extra rates of the whole buildings at full speed, found by scaling the solved
+    /// rates. Only available in integer mode.
+    pub fn spare_capacity(&self) -> Option<&SpareCapacity> {
+        self.spare_capacity.as_ref()
+    }
+}
+
+/// Tarjan's algorithm. Each component is sorted.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct State<'a> {
+        edges: &'a [Vec<usize>],
+        next_index: usize,
+        index: Vec<Option<usize>>,
+        low_link: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        components: Vec<Vec<usize>>,
+    }
+
+    fn visit(state: &mut State, node: usize) {
+        state.index[node] = Some(state.next_index);
+        state.low_link[node] = state.next_index;
+        state.next_index += 1;
+        state.stack.push(node);
+        state.on_stack[node] = true;
+        for &next in &state.edges[node] {
+            if let Some(next_index) = state.index[next] {
+                if state.on_stack[next] {
+                    state.low_link[node] = state.low_link[node].min(next_index);
+                }
+            } else {
+                visit(state, next);
+                state.low_link[node] = state.low_link[node].min(state.low_link[next]);
+            }
+        }
+        if Some(state.low_link[node]) == state.index[node] {
+            let mut component = Vec::new();
+            while let Some(other) = state.stack.pop() {
+                state.on_stack[other] = false;
+                component.push(other);
+                if other == node {
+                    break;
+                }
+            }
+            component.sort_unstable();
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        edges,
+        next_index: 0,
+        index: vec![None; edges.len()],
+        low_link: vec![0; edges.len()],
+        on_stack: vec![false; edges.len()],
+        stack: Vec::new(),
+        components: Vec::new(),
+    };
+    for node in 0..edges.len() {
+        if state.index[node].is_none() {
+            visit(&mut state, node);
+        }
+    }
+    state.components
+}
+
+// Weight of the rows that must hold in `solve_with_upper_bounds`.
+const HARD_ROW_WEIGHT: f64 = 1e4;
+
+/// Solves `a * x = b` subject to `x >= 0`, except that rows in `upper_bounds` are
+/// `a * x <= b`, using a non-negative slack variable for each of them. Rows in `soft` are
+/// fitted with least squares after all other rows hold, so they are violated if they don't fit
+/// the bounds. Without soft rows, the solution gets as close to the upper bounds as possible.
+fn solve_with_upper_bounds(
+    a: &DMatrix<f64>,
+    b: &DVector<f64>,
+    soft: &[usize],
+    upper_bounds: &[usize],
+) -> DVector<f64> {
+    let n = a.ncols();
+    let extra_rows = if soft.is_empty() { upper_bounds } else { &[] };
+    let mut matrix = DMatrix::zeros(a.nrows() + extra_rows.len(), n + upper_bounds.len());
+    let mut values = DVector::zeros(a.nrows() + extra_rows.len());
+    for row in 0..a.nrows() {
+        let weight = if soft.contains(&row) {
+            1.0
+        } else {
+            HARD_ROW_WEIGHT
+        };
+        for col in 0..n {
+            matrix[(row, col)] = a[(row, col)] * weight;
+        }
+        values[row] = b[row] * weight;
+        if let Some(slack) = upper_bounds.iter().position(|r| *r == row) {
+            matrix[(row, n + slack)] = weight;
+        }
+    }
+    for (index, row) in extra_rows.iter().enumerate() {
+        for col in 0..n {
+            matrix[(a.nrows() + index, col)] = a[(*row, col)];
+        }
+        values[a.nrows() + index] = b[*row];
+    }
+    non_negative_least_squares(&matrix, &values)
+        .rows(0, n)
+        .into_owned()
+}
+
+/// Lawson-Hanson algorithm: minimizes |Ax - b| subject to x >= 0.
+fn non_negative_least_squares(a: &DMatrix<f64>, b: &DVector<f64>) -> DVector<f64> {
+    const TOLERANCE: f64 = 1e-10;
+    let n = a.ncols();
+    let mut x = DVector::zeros(n);
+    let mut passive = vec![false; n];
+
+    // Least squares solution using only passive columns.
+    let solve_passive = |passive: &[bool]| {
+        let columns = (0..n).filter(|j| passive[*j]).collect_vec();
+        let sub = DMatrix::from_fn(a.nrows(), columns.len(), |row, col| a[(row, columns[col])]);
+        let mut z = DVector::zeros(n);
+        if columns.is_empty() {
+            return z;
+        }
+        if let Ok(solution) = sub.svd(true, true).solve(b, TOLERANCE) {
+            for (value, column) in solution.iter().zip(&columns) {
+                z[*column] = *value;
+            }
+        }
+        z
+    };
+
+    for _ in 0..3 * n.max(1) {
+        let gradient = a.transpose() * (b - a * &x);
+        let Some(next) = (0..n)
+            .filter(|j| !passive[*j] && gradient[*j] > TOLERANCE)
+            .max_by(|i, j| gradient[*i].total_cmp(&gradient[*j]))
+        else {
+            break;
+        };
+        passive[next] = true;
+        loop {
+            let z = solve_passive(&passive);
+            if (0..n).filter(|j| passive[*j]).all(|j| z[j] > TOLERANCE) {
+                x = z;
+                break;
+            }
+            let alpha = (0..n)
+                .filter(|j| passive[*j] && z[*j] <= TOLERANCE && x[*j] > z[*j])
+                .map(|j| x[j] / (x[j] - z[j]))
+                .fold(1.0, f64::min);
+            x += (z - &x) * alpha;
+            for j in 0..n {
+                if passive[j] && x[j] <= TOLERANCE {
+                    passive[j] = false;
+                    x[j] = 0.0;
+                }
+            }
+        }
+    }
+    x
 }
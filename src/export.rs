@@ -0,0 +1,351 @@
+use {
+    crate::{
+        bom::BillOfMaterials,
+        editor::Editor,
+        flowchart::item_flows,
+        machine::{ItemSpeed, Module},
+        module_counts, rf,
+        snippet::MachineSnippet,
+    },
+    itertools::Itertools,
+    serde::Serialize,
+    std::{collections::BTreeMap, fmt::Write},
+};
+
+/// A format the solved plan can be exported to.
+pub trait Exporter {
+    /// Name used in the UI and on the command line.
+    fn name(&self) -> &'static str;
+    fn extension(&self) -> &'static str;
+    fn export(&self, editor: &Editor) -> anyhow::Result<String>;
+}
+
+pub fn exporters() -> Vec<Box<dyn Exporter>> {
+    vec![
+        Box::new(TextExporter),
+        Box::new(JsonExporter),
+        Box::new(CsvExporter),
+        Box::new(MarkdownExporter),
+        Box::new(BillOfMaterialsExporter),
+    ]
+}
+
+pub fn exporter(name: &str) -> anyhow::Result<Box<dyn Exporter>> {
+    exporters()
+        .into_iter()
+        .find(|e| e.name() == name)
+        .ok_or_else(|| {
+            anyhow::format_err!(
+                "unknown export format: {name:?}, available formats: {}",
+                exporters().iter().map(|e| e.name()).join(", ")
+            )
+        })
+}
+
+/// Plain text description, same as "Copy description" in the UI.
+pub struct TextExporter;
+
+impl Exporter for TextExporter {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn export(&self, editor: &Editor) -> anyhow::Result<String> {
+        Ok(editor.description())
+    }
+}
+
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn export(&self, editor: &Editor) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&Plan::new(editor))?)
+    }
+}
+
+/// Machines with unrounded counts and rates per second.
+pub struct CsvExporter;
+
+impl Exporter for CsvExporter {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn export(&self, editor: &Editor) -> anyhow::Result<String> {
+        let plan = Plan::new(editor);
+        let mut out = String::new();
+        writeln!(
+            out,
+            "kind,crafter,recipe,count,modules,beacons,inputs,outputs"
+        )?;
+        for machine in &plan.machines {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{}",
+                machine.kind,
+                csv_field(&machine.crafter),
+                csv_field(&machine.recipe),
+                machine.count,
+                csv_field(&counts_text(&machine.modules)),
+                csv_field(&beacons_text(&machine.beacons)),
+                csv_field(&raw_rates_text(&machine.inputs)),
+                csv_field(&raw_rates_text(&machine.outputs)),
+            )?;
+        }
+        Ok(out)
+    }
+}
+
+pub struct MarkdownExporter;
+
+impl Exporter for MarkdownExporter {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn export(&self, editor: &Editor) -> anyhow::Result<String> {
+        let plan = Plan::new(editor);
+        let mut out = String::new();
+        writeln!(
+            out,
+            "| Count | Crafter | Recipe | Modules | Beacons | Inputs | Outputs |"
+        )?;
+        writeln!(out, "|---|---|---|---|---|---|---|")?;
+        for machine in plan.machines.iter().filter(|m| m.kind == "crafter") {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                rf(machine.count),
+                machine.crafter,
+                machine.recipe,
+                counts_text(&machine.modules),
+                beacons_text(&machine.beacons).replace('\n', "<br>"),
+                rates_text(editor, &machine.inputs),
+                rates_text(editor, &machine.outputs),
+            )?;
+        }
+        writeln!(out)?;
+        writeln!(out, "| Item | Production | Consumption | Net |")?;
+        writeln!(out, "|---|---|---|---|")?;
+        for item in &plan.items {
+            let rate = |rate: f64| {
+                editor
+                    .info()
+                    .format_speed(&item.item.as_str().into(), rate.into())
+            };
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                item.item,
+                rate(item.production),
+                rate(item.consumption),
+                rate(item.net)
+            )?;
+        }
+        Ok(out)
+    }
+}
+
+/// Items needed to build the factory, without belts and inserters.
+pub struct BillOfMaterialsExporter;
+
+impl Exporter for BillOfMaterialsExporter {
+    fn name(&self) -> &'static str {
+        "bill-of-materials"
+    }
+
+    fn extension(&self) -> &'static str {
+        "bom.txt"
+    }
+
+    fn export(&self, editor: &Editor) -> anyhow::Result<String> {
+        Ok(BillOfMaterials::new(
+            editor.info(),
+            editor.machines().iter().map(|m| m.machine()),
+            editor.power_plant_buildings(),
+            false,
+        )
+        .description())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Plan {
+    solved: bool,
+    machines: Vec<PlanMachine>,
+    items: Vec<PlanItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlanMachine {
+    /// "source", "sink" or "crafter"
+    kind: &'static str,
+    crafter: String,
+    recipe: String,
+    count: f64,
+    modules: BTreeMap<String, usize>,
+    beacons: Vec<PlanBeacon>,
+    inputs: BTreeMap<String, f64>,
+    outputs: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlanBeacon {
+    #[serde(rename = "type")]
+    type_: String,
+    quality: u32,
+    modules: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlanItem {
+    item: String,
+    production: f64,
+    consumption: f64,
+    net: f64,
+}
+
+impl Plan {
+    fn new(editor: &Editor) -> Self {
+        let machines = editor
+            .machines()
+            .iter()
+            .map(|editor_machine| {
+                let machine = editor_machine.machine();
+                PlanMachine {
+                    kind: match editor_machine.snippet() {
+                        MachineSnippet::Source(_) => "source",
+                        MachineSnippet::Sink(_) => "sink",
+                        MachineSnippet::Crafter(_) => "crafter",
+                    },
+                    crafter: machine.crafter.name.to_string(),
+                    recipe: machine.recipe.name.to_string(),
+                    count: machine.crafter_count,
+                    modules: counts(&machine.modules),
+                    beacons: machine
+                        .beacons
+                        .iter()
+                        .map(|beacon| PlanBeacon {
+                            type_: beacon.type_.name.to_string(),
+                            quality: beacon.quality,
+                            modules: counts(&beacon.modules),
+                        })
+                        .collect(),
+                    inputs: rates(machine.input_speeds().map(|i| ItemSpeed {
+                        item: i.item,
+                        speed: -i.speed,
+                    })),
+                    outputs: rates(machine.output_speeds()),
+                }
+            })
+            .collect();
+        let items = item_flows(editor)
+            .into_iter()
+            .map(|flow| {
+                let production = flow.sources.iter().map(|(_, s)| f64::from(*s)).sum();
+                let consumption = flow.destinations.iter().map(|(_, s)| f64::from(*s)).sum();
+                PlanItem {
+                    item: flow.item.to_string(),
+                    production,
+                    consumption,
+                    net: production - consumption,
+                }
+            })
+            .collect();
+        Plan {
+            solved: editor.solved(),
+            machines,
+            items,
+        }
+    }
+}
+
+fn counts(modules: &[Module]) -> BTreeMap<String, usize> {
+    module_counts(modules)
+        .into_iter()
+        .map(|(name, count)| (name.to_string(), count))
+        .collect()
+}
+
+fn rates(speeds: impl Iterator<Item = ItemSpeed>) -> BTreeMap<String, f64> {
+    let mut rates = BTreeMap::<String, f64>::new();
+    for item_speed in speeds {
+        *rates.entry(item_speed.item.to_string()).or_default() += f64::from(item_speed.speed);
+    }
+    rates
+}
+
+fn counts_text(counts: &BTreeMap<String, usize>) -> String {
+    counts
+        .iter()
+        .map(|(name, count)| format!("{count} × {name}"))
+        .join(", ")
+}
+
+fn beacons_text(beacons: &[PlanBeacon]) -> String {
+    beacons
+        .iter()
+        .map(|beacon| {
+            let quality = if beacon.quality > 0 {
+                format!(", quality {}", beacon.quality)
+            } else {
+                String::new()
+            };
+            format!(
+                "{}({}{quality})",
+                beacon.type_,
+                counts_text(&beacon.modules)
+            )
+        })
+        .join("\n")
+}
+
+fn rates_text(editor: &Editor, rates: &BTreeMap<String, f64>) -> String {
+    rates
+        .iter()
+        .map(|(item, rate)| {
+            format!(
+                "{} {item}",
+                editor
+                    .info()
+                    .format_speed(&item.as_str().into(), (*rate).into())
+            )
+        })
+        .join(" + ")
+}
+
+// Unrounded rates per second, for machine-readable exports.
+fn raw_rates_text(rates: &BTreeMap<String, f64>) -> String {
+    rates
+        .iter()
+        .map(|(item, rate)| format!("{rate} {item}"))
+        .join(" + ")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,10 +1,179 @@
 use {
-    crate::{editor::Editor, primitives::Speed, rf, snippet::MachineSnippet},
+    crate::{
+        config::EdgeAllocation,
+        editor::Editor,
+        info::Info,
+        primitives::{ItemName, Speed},
+        snippet::MachineSnippet,
+        ResultExtOrWarn,
+    },
+    anyhow::ensure,
     itertools::Itertools,
-    std::{cmp::min, collections::VecDeque, fmt::Write},
-    tracing::warn,
+    std::{cmp::min, fmt::Write},
 };
 
+const EPSILON: f64 = 0.001;
+
+// Throughput of a pipeline in Factorio 2.0 doesn't depend on its length.
+const PIPE_THROUGHPUT: f64 = 6000.0;
+
+/// Producers and consumers of an item, as `(machine_index, speed)`. All speeds are positive.
+#[derive(Debug, Clone)]
+pub struct ItemFlow {
+    pub item: ItemName,
+    pub sources: Vec<(usize, Speed)>,
+    pub destinations: Vec<(usize, Speed)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub source: usize,
+    pub destination: usize,
+    pub item: ItemName,
+    pub speed: Speed,
+}
+
+pub fn item_flows(editor: &Editor) -> Vec<ItemFlow> {
+    editor
+        .added_items()
+        .into_iter()
+        .map(|item| {
+            let sources = editor
+                .machines()
+                .iter()
+                .enumerate()
+                .filter_map(|(machine_index, machine)| {
+                    machine
+                        .machine()
+                        .net_item_speeds()
+                        .into_iter()
+                        .find(|item_speed| {
+                            item_speed.item == item && item_speed.speed > Speed::ZERO
+                        })
+                        .map(|item_speed| (machine_index, item_speed.speed))
+                })
+                .collect_vec();
+
+            let destinations = editor
+                .machines()
+                .iter()
+                .enumerate()
+                .filter_map(|(machine_index, machine)| {
+                    machine
+                        .machine()
+                        .net_item_speeds()
+                        .into_iter()
+                        .find(|item_speed| {
+                            item_speed.item == item && item_speed.speed < Speed::ZERO
+                        })
+                        .map(|item_speed| (machine_index, -item_speed.speed))
+                })
+                .collect_vec();
+
+            ItemFlow {
+                item,
+                sources,
+                destinations,
+            }
+        })
+        .collect()
+}
+
+/// Fails if the producers of the item don't match its consumers,
+/// so `allocate` can't connect all of them.
+pub fn check_balance(flow: &ItemFlow) -> anyhow::Result<()> {
+    let supply: Speed = flow.sources.iter().map(|(_, speed)| *speed).sum();
+    let demand: Speed = flow.destinations.iter().map(|(_, speed)| *speed).sum();
+    ensure!(
+        supply - demand < Speed::from(EPSILON),
+        "unable to allocate remaining {} {} to destinations",
+        supply - demand,
+        flow.item
+    );
+    ensure!(
+        demand - supply < Speed::from(EPSILON),
+        "not all destinations of {} are satisfied: missing {}",
+        flow.item,
+        demand - supply
+    );
+    Ok(())
+}
+
+/// Splits output of the item's producers between its consumers.
+pub fn allocate(flow: &ItemFlow, mode: EdgeAllocation) -> Vec<Edge> {
+    let epsilon = Speed::from(EPSILON);
+    let mut edges = Vec::new();
+    let mut add_edge = |source, destination, speed| {
+        edges.push(Edge {
+            source,
+            destination,
+            item: flow.item.clone(),
+            speed,
+        });
+    };
+    let mut sources = flow.sources.clone();
+    let mut destinations = flow.destinations.clone();
+    match mode {
+        EdgeAllocation::MinEdges => loop {
+            sources.retain(|(_, speed)| *speed >= epsilon);
+            destinations.retain(|(_, speed)| *speed >= epsilon);
+            if sources.is_empty() || destinations.is_empty() {
+                break;
+            }
+            // A source that exactly covers a destination needs only one edge,
+            // so handle such pairs first.
+            let exact_match = sources.iter().enumerate().find_map(|(si, (_, s))| {
+                destinations
+                    .iter()
+                    .position(|(_, d)| f64::from(*s - *d).abs() < EPSILON)
+                    .map(|di| (si, di))
+            });
+            if let Some((si, di)) = exact_match {
+                let (source, speed) = sources.remove(si);
+                let (destination, _) = destinations.remove(di);
+                add_edge(source, destination, speed);
+                continue;
+            }
+            // Otherwise connect the largest source to the largest destination,
+            // which fully consumes one of them.
+            sources.sort_by_key(|(_, speed)| -*speed);
+            destinations.sort_by_key(|(_, speed)| -*speed);
+            let speed = min(sources[0].1, destinations[0].1);
+            add_edge(sources[0].0, destinations[0].0, speed);
+            sources[0].1 -= speed;
+            destinations[0].1 -= speed;
+        },
+        EdgeAllocation::Even => {
+            let total_demand: Speed = destinations.iter().map(|(_, speed)| *speed).sum();
+            if total_demand >= epsilon {
+                for (source, source_speed) in &sources {
+                    for (destination, destination_speed) in &destinations {
+                        let speed = *source_speed * (*destination_speed / total_demand);
+                        if speed >= epsilon {
+                            add_edge(*source, *destination, speed);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// Number of belts or pipes needed to carry `speed` of `item`, e.g. "2 × fast-transport-belt".
+/// Returns `true` as the second element if the flow doesn't fit in one lane of the belt.
+pub fn transport_text(info: &Info, item: &ItemName, speed: Speed) -> (String, bool) {
+    if info.is_fluid(item) {
+        let count = (f64::from(speed) / PIPE_THROUGHPUT).ceil();
+        (format!("{} × pipe", count), false)
+    } else if let Some((belt_speed, belt)) = info.belt() {
+        let count = (speed / belt_speed).ceil();
+        (format!("{} × {}", count, belt), speed > belt_speed * 0.5)
+    } else {
+        (String::new(), false)
+    }
+}
+
 pub fn generate(editor: &Editor, title: &str) -> String {
     let mut out = String::new();
     if !title.is_empty() {
@@ -34,20 +203,20 @@ pub fn generate(editor: &Editor, title: &str) -> String {
             if machine.crafter.is_source_or_sink() {
                 String::new()
             } else {
-                format!("{} × ", rf(machine.crafter_count))
+                format!("{} × ", editor_machine.count_text())
             },
             machine.crafter.name,
             if machine.crafter.is_source_or_sink() {
                 machine
                     .recipe
                     .ingredients
-                    .get(0)
+                    .first()
                     .map(|i| &i.name)
                     .unwrap_or_else(|| {
                         machine
                             .recipe
                             .products
-                            .get(0)
+                            .first()
                             .map(|i| &i.name)
                             .expect("invalid source or sink recipe")
                     })
@@ -60,74 +229,29 @@ pub fn generate(editor: &Editor, title: &str) -> String {
         .unwrap();
     }
 
-    let all_items = editor.added_items();
-    for item in all_items {
-        let sources = editor
-            .machines()
-            .iter()
-            .enumerate()
-            .filter_map(|(machine_index, machine)| {
-                machine
-                    .machine()
-                    .item_speeds()
-                    .into_iter()
-                    .find(|item_speed| item_speed.item == item && item_speed.speed > Speed::ZERO)
-                    .map(|item_speed| (machine_index, item_speed.speed))
-            })
-            .collect_vec();
-
-        let mut destinations: VecDeque<_> = editor
-            .machines()
-            .iter()
-            .enumerate()
-            .filter_map(|(machine_index, machine)| {
-                machine
-                    .machine()
-                    .item_speeds()
-                    .into_iter()
-                    .find(|item_speed| item_speed.item == item && item_speed.speed < Speed::ZERO)
-                    .map(|item_speed| (machine_index, -item_speed.speed))
-            })
-            .collect();
-
-        let epsilon = Speed::from(0.001);
-        'outer: for (source_machine, source_speed) in sources {
-            let mut remaining_speed = source_speed;
-            loop {
-                let Some((destination_machine, destination_speed)) = destinations.front_mut()
-                else {
-                    warn!(
-                        "unable to allocate remaining {}/s {} to destinations",
-                        remaining_speed, item
-                    );
-                    break 'outer;
-                };
-                let current_speed = min(remaining_speed, *destination_speed);
-                writeln!(
-                    out,
-                    "    machine{}-->|{} *{}*|machine{}",
-                    source_machine, current_speed, item, destination_machine
-                )
-                .unwrap();
-                *destination_speed -= current_speed;
-                if *destination_speed < epsilon {
-                    destinations.pop_front().unwrap();
-                }
-                remaining_speed -= current_speed;
-                if remaining_speed < epsilon {
-                    break; // Move on to the next source machine.
-                }
-            }
-        }
-        if destinations.len() > 2
-            || destinations
-                .front()
-                .is_some_and(|(_, speed)| *speed > epsilon)
-        {
-            warn!(
-                "not all destinations of {} are satisfied: {:?}",
-                item, destinations
-            );
+    let mode = editor.info().config.edge_allocation;
+    for flow in item_flows(editor) {
+        check_balance(&flow).or_warn();
+        for edge in allocate(&flow, mode) {
+            let (transport, exceeds_lane) = transport_text(editor.info(), &edge.item, edge.speed);
+            // Machines are sorted so that producers go first, so an edge going up
+            // is part of a cycle. Draw it dotted so the loop is easy to see.
+            let arrow = if edge.destination <= edge.source {
+                "-.->"
+            } else {
+                "-->"
+            };
+            writeln!(
+                out,
+                r#"    machine{}{arrow}|"{} *{}*<br>{}{}"|machine{}"#,
+                edge.source,
+                editor.info().format_speed(&edge.item, edge.speed),
+                edge.item,
+                if exceeds_lane { "⚠ " } else { "" },
+                transport,
+                edge.destination
+            )
+            .unwrap();
         }
     }
     out
@@ -1,5 +1,5 @@
 use {
-    crate::primitives::{Amount, ItemName, RecipeCategory, RecipeName},
+    crate::primitives::{Amount, ItemName, PlanetName, RecipeCategory, RecipeName, TechnologyName},
     anyhow::Context,
     serde::{Deserialize, Deserializer, Serialize},
     std::collections::BTreeMap,
@@ -49,6 +49,27 @@ pub struct Recipe {
     pub order: String,
     pub productivity_bonus: f64,
     pub allowed_effects: Effects,
+    /// Upper limit of the productivity bonus, e.g. 3.0 for +300%.
+    #[serde(default = "default_maximum_productivity")] // only for compatibility
+    pub maximum_productivity: f64,
+    #[serde(default, deserialize_with = "deserialize_array_or_object")]
+    pub surface_conditions: Vec<SurfaceCondition>,
+    /// Multiplier of the crafter's emissions when it crafts this recipe.
+    #[serde(default = "default_emissions_multiplier")] // only for compatibility
+    pub emissions_multiplier: f64,
+}
+
+fn default_maximum_productivity() -> f64 {
+    Recipe::DEFAULT_MAXIMUM_PRODUCTIVITY
+}
+
+fn default_emissions_multiplier() -> f64 {
+    1.0
+}
+
+impl Recipe {
+    /// Productivity cap of recipes in Space Age.
+    pub const DEFAULT_MAXIMUM_PRODUCTIVITY: f64 = 3.0;
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
@@ -69,10 +90,13 @@ pub struct Ingredient {
     pub amount: Amount,
 }
 
-// Skipped "fluidbox_index", "ignored_by_stats", "percent_spoiled" properties
+// Skipped "fluidbox_index", "ignored_by_stats" properties
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Product {
     pub amount: Amount,
+    /// Spoilage of the product when it's crafted, from 0 to 1.
+    #[serde(default)]
+    pub percent_spoiled: f64,
     #[serde(default)]
     pub ignored_by_productivity: Amount,
     pub name: ItemName,
@@ -103,6 +127,43 @@ pub struct Entity {
     pub mineable_properties: Option<MineableProperties>,
     pub resource_category: Option<String>,
     pub module_inventory_size: u64,
+    // Beacon properties
+    pub distribution_effectivity: Option<f64>,
+    pub distribution_effectivity_bonus_per_quality_level: Option<f64>,
+    /// Transmission strength multiplier by the number of beacons affecting a machine (starting at 1).
+    pub profile: Option<Vec<f64>>,
+    /// "same_type" or "total"
+    pub beacon_counter: Option<String>,
+    pub allowed_effects: Option<Effects>,
+    #[serde(default, deserialize_with = "deserialize_array_or_object")]
+    pub surface_conditions: Vec<SurfaceCondition>,
+    // Rocket silo properties
+    #[serde(default)]
+    pub rocket_parts_required: Option<u32>,
+    /// Emissions at full power by pollutant (e.g. "pollution", "spores"), per minute.
+    #[serde(default)]
+    pub emissions_per_minute: BTreeMap<String, f64>,
+    // Power properties, per tick
+    #[serde(default)]
+    pub pumping_speed: Option<f64>,
+    #[serde(default)]
+    pub max_energy_usage: Option<f64>,
+    #[serde(default)]
+    pub max_energy_production: Option<f64>,
+    #[serde(default)]
+    pub fluid_usage_per_tick: Option<f64>,
+    /// Reactor output bonus per neighbouring reactor, e.g. 1 for +100%.
+    #[serde(default)]
+    pub neighbour_bonus: Option<f64>,
+    /// Energy stored by an accumulator, in joules.
+    #[serde(default)]
+    pub buffer_capacity: Option<f64>,
+    // Wagon properties
+    /// Number of item stacks of a cargo wagon.
+    #[serde(default)]
+    pub inventory_size: Option<u64>,
+    #[serde(default)]
+    pub fluid_capacity: Option<f64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -118,6 +179,119 @@ pub struct MineableProperties {
 pub struct GameData {
     pub recipes: BTreeMap<RecipeName, Recipe>,
     pub entities: BTreeMap<ItemName, Entity>,
+    #[serde(default)] // only for compatibility
+    pub technologies: BTreeMap<TechnologyName, Technology>,
+    #[serde(default)] // only for compatibility
+    pub planets: BTreeMap<PlanetName, Planet>,
+    #[serde(default)] // only for compatibility
+    pub items: BTreeMap<ItemName, ItemPrototype>,
+    /// Maximum total weight of a rocket's cargo, in the same units as item weights.
+    #[serde(default)] // only for compatibility
+    pub rocket_lift_weight: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemPrototype {
+    pub name: ItemName,
+    /// Time until the item spoils (60 ticks per second), 0 if it doesn't spoil.
+    #[serde(default)]
+    pub spoil_ticks: u64,
+    /// Item that the spoiled item turns into, e.g. "spoilage".
+    pub spoil_result: Option<ItemName>,
+    /// Weight of one item (1000 per kg). Determines how many items fit into a rocket.
+    #[serde(default)]
+    pub weight: Option<f64>,
+    #[serde(default)]
+    pub module_effects: Option<ModuleEffects>,
+    #[serde(default)]
+    pub stack_size: Option<u64>,
+}
+
+/// Effects of a module, e.g. 0.05 for +5%.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ModuleEffects {
+    pub consumption: f64,
+    pub speed: f64,
+    pub productivity: f64,
+    pub pollution: f64,
+    pub quality: f64,
+}
+
+impl ItemPrototype {
+    /// Spoil time in seconds, or `None` if the item doesn't spoil.
+    pub fn spoil_time(&self) -> Option<f64> {
+        (self.spoil_ticks > 0).then(|| self.spoil_ticks as f64 / 60.0)
+    }
+}
+
+/// Allowed range of a surface property (e.g. "pressure") for a recipe or an entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceCondition {
+    pub property: String,
+    #[serde(default = "default_condition_min")]
+    pub min: f64,
+    #[serde(default = "default_condition_max")]
+    pub max: f64,
+}
+
+fn default_condition_min() -> f64 {
+    f64::MIN
+}
+
+fn default_condition_max() -> f64 {
+    f64::MAX
+}
+
+impl SurfaceCondition {
+    /// Properties missing on the planet are not checked.
+    pub fn is_met_on(&self, planet: &Planet) -> bool {
+        planet
+            .surface_properties
+            .get(&self.property)
+            .is_none_or(|value| (self.min..=self.max).contains(value))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Planet {
+    pub name: PlanetName,
+    /// Values of all surface properties, e.g. "pressure" = 1000.
+    pub surface_properties: BTreeMap<String, f64>,
+    /// Resource entities that are placed on the planet's map.
+    #[serde(default, deserialize_with = "deserialize_array_or_object")]
+    pub resources: Vec<ItemName>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Technology {
+    pub name: TechnologyName,
+    #[serde(default, deserialize_with = "deserialize_array_or_object")]
+    pub effects: Vec<TechnologyEffect>,
+    // Technologies with levels (e.g. "mining-productivity-3") can be researched
+    // from `level` to `max_level`. `max_level` is 4294967295 for infinite technologies.
+    #[serde(default = "default_level")]
+    pub level: u32,
+    #[serde(default = "default_level")]
+    pub max_level: u32,
+}
+
+fn default_level() -> u32 {
+    1
+}
+
+// Only properties of effects that change recipes are kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TechnologyEffect {
+    // "unlock-recipe", "change-recipe-productivity", "mining-drill-productivity-bonus",
+    // "laboratory-speed", ...
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub recipe: Option<RecipeName>,
+    // For "change-recipe-productivity", e.g. 0.1 for +10%.
+    pub change: Option<f64>,
+    // For bonuses like "mining-drill-productivity-bonus", e.g. 0.1 for +10%.
+    pub modifier: Option<f64>,
 }
 
 impl GameData {
@@ -1,11 +1,16 @@
 use {
     crate::{
-        config::Config,
-        game_data::GameData,
-        machine::{Crafter, Module, ModuleType},
-        primitives::{CrafterName, ItemName, ModuleName, RecipeCategory},
+        config::{Config, RateUnit, RecipeFilter, CONFIG_PATH},
+        game_data::{GameData, Planet, Recipe},
+        machine::{BeaconType, Crafter, Module, ModuleType},
+        primitives::{
+            BeaconName, CrafterName, ItemName, ModuleName, RecipeCategory, RecipeName, Speed,
+        },
+        research::ResearchLevels,
+        rf, ResultExtOrWarn,
     },
     anyhow::{bail, Context},
+    itertools::Itertools,
     std::{
         collections::{BTreeMap, BTreeSet},
         env,
@@ -14,14 +19,102 @@ use {
     tracing::{info, trace},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Info {
     pub config: Config,
     pub game_data: GameData,
     pub modules: BTreeMap<ModuleName, Module>,
     pub all_items: BTreeSet<ItemName>,
     pub crafters: BTreeMap<CrafterName, Crafter>,
+    pub beacons: BTreeMap<BeaconName, BeaconType>,
     pub category_to_crafter: BTreeMap<RecipeCategory, Vec<CrafterName>>,
+    /// Belt throughputs sorted from slowest to fastest.
+    pub belt_speeds: Vec<(Speed, ItemName)>,
+    pub fluids: BTreeSet<ItemName>,
+    /// Recipes that are enabled in game data or unlocked by the research in use.
+    pub unlocked_recipes: BTreeSet<RecipeName>,
+    /// Crafters that can only be built with recipes that are not unlocked.
+    pub locked_crafters: BTreeSet<CrafterName>,
+    /// Planet of the snippet. Recipes and crafters are filtered by its surface conditions.
+    pub planet: Option<Planet>,
+}
+
+/// Modules of the base game, used if game data doesn't contain items with module effects.
+fn default_modules() -> Vec<Module> {
+    vec![
+        Module {
+            name: "speed-module".into(),
+            type_: ModuleType::Speed,
+            energy_delta_percent: 50.,
+            speed_delta_percent: 20.,
+            productivity_delta_percent: 0.,
+            pollution_delta_percent: 0.,
+        },
+        Module {
+            name: "speed-module-2".into(),
+            type_: ModuleType::Speed,
+            energy_delta_percent: 60.,
+            speed_delta_percent: 30.,
+            productivity_delta_percent: 0.,
+            pollution_delta_percent: 0.,
+        },
+        Module {
+            name: "speed-module-3".into(),
+            type_: ModuleType::Speed,
+            energy_delta_percent: 70.,
+            speed_delta_percent: 50.,
+            productivity_delta_percent: 0.,
+            pollution_delta_percent: 0.,
+        },
+        Module {
+            name: "efficiency-module".into(),
+            type_: ModuleType::Efficiency,
+            energy_delta_percent: -30.,
+            speed_delta_percent: 0.,
+            productivity_delta_percent: 0.,
+            pollution_delta_percent: 0.,
+        },
+        Module {
+            name: "efficiency-module-2".into(),
+            type_: ModuleType::Efficiency,
+            energy_delta_percent: -40.,
+            speed_delta_percent: 0.,
+            productivity_delta_percent: 0.,
+            pollution_delta_percent: 0.,
+        },
+        Module {
+            name: "efficiency-module-3".into(),
+            type_: ModuleType::Efficiency,
+            energy_delta_percent: -50.,
+            speed_delta_percent: 0.,
+            productivity_delta_percent: 0.,
+            pollution_delta_percent: 0.,
+        },
+        Module {
+            name: "productivity-module".into(),
+            type_: ModuleType::Productivity,
+            energy_delta_percent: 40.,
+            speed_delta_percent: -5.,
+            productivity_delta_percent: 4.0,
+            pollution_delta_percent: 5.,
+        },
+        Module {
+            name: "productivity-module-2".into(),
+            type_: ModuleType::Productivity,
+            energy_delta_percent: 60.,
+            speed_delta_percent: -10.,
+            productivity_delta_percent: 6.0,
+            pollution_delta_percent: 7.,
+        },
+        Module {
+            name: "productivity-module-3".into(),
+            type_: ModuleType::Productivity,
+            energy_delta_percent: 80.,
+            speed_delta_percent: -15.,
+            productivity_delta_percent: 10.0,
+            pollution_delta_percent: 10.,
+        },
+    ]
 }
 
 impl Info {
@@ -44,7 +137,7 @@ impl Info {
             }
         }
 
-        let config: Config = toml::from_str(&fs_err::read_to_string("config.toml")?)?;
+        let config: Config = toml::from_str(&fs_err::read_to_string(CONFIG_PATH)?)?;
         let mut game_data: GameData =
             serde_json::from_str(&fs_err::read_to_string("game_data.json")?)?;
 
@@ -63,15 +156,46 @@ impl Info {
         });
 
         let mut all_items = BTreeSet::new();
+        let mut fluids = BTreeSet::new();
         for recipe in game_data.recipes.values() {
             for item in &recipe.ingredients {
                 all_items.insert(item.name.clone());
+                if item.type_ == "fluid" {
+                    fluids.insert(item.name.clone());
+                }
             }
             for item in &recipe.products {
                 all_items.insert(item.name.clone());
+                if item.type_ == "fluid" {
+                    fluids.insert(item.name.clone());
+                }
             }
         }
 
+        let mut belt_speeds = game_data
+            .entities
+            .values()
+            .filter(|e| e.type_ == "transport-belt")
+            .map(|e| {
+                // belt_speed is tiles per tick;
+                // throughput per second = belt_speed * 60 (ticks/s) * 8 (density)
+                anyhow::Ok((
+                    Speed::from(e.belt_speed.context("missing belt_speed")? * 60. * 8.),
+                    e.name.clone(),
+                ))
+            })
+            .try_collect::<_, Vec<_>, _>()?;
+        belt_speeds.sort_by_key(|(speed, _)| *speed);
+        if let Some(name) = &config.belt_type {
+            belt_speeds
+                .iter()
+                .find(|(_, belt)| belt == name)
+                .with_context(|| {
+                    format!("unknown belt_type in config: {name}, using the fastest belt instead")
+                })
+                .or_warn();
+        }
+
         let mut crafters = BTreeMap::new();
         let mut category_to_crafter = BTreeMap::<_, Vec<_>>::new();
         for entity in game_data.entities.values() {
@@ -96,59 +220,80 @@ impl Info {
                             format!("missing crafting_speed for crafter: {entity:?}")
                         })?,
                         module_inventory_size: entity.module_inventory_size,
+                        emissions_per_minute: entity.emissions_per_minute.clone(),
                     },
                 );
             }
         }
 
+        let mut beacons = BTreeMap::new();
+        for entity in game_data.entities.values() {
+            if entity.type_ != "beacon" {
+                continue;
+            }
+            let vanilla = BeaconType::vanilla();
+            beacons.insert(
+                entity.name.as_str().into(),
+                BeaconType {
+                    name: entity.name.as_str().into(),
+                    distribution_effectivity: entity
+                        .distribution_effectivity
+                        .unwrap_or(vanilla.distribution_effectivity),
+                    distribution_effectivity_bonus_per_quality_level: entity
+                        .distribution_effectivity_bonus_per_quality_level
+                        .unwrap_or(vanilla.distribution_effectivity_bonus_per_quality_level),
+                    profile: entity.profile.clone().unwrap_or(vanilla.profile),
+                    count_same_type_only: entity.beacon_counter.as_deref() == Some("same_type"),
+                    module_inventory_size: entity.module_inventory_size,
+                    energy_usage: entity
+                        .energy_usage
+                        .with_context(|| format!("missing energy_usage for beacon: {entity:?}"))?,
+                    allowed_effects: entity
+                        .allowed_effects
+                        .clone()
+                        .unwrap_or(vanilla.allowed_effects),
+                },
+            );
+        }
+        if beacons.is_empty() {
+            let vanilla = BeaconType::vanilla();
+            beacons.insert(vanilla.name.clone(), vanilla);
+        }
+
         for (category, crafters) in &category_to_crafter {
             trace!("{}: {}     {:?}", category, crafters.len(), crafters);
         }
 
-        let modules = [
-            Module {
-                name: "speed-module".into(),
-                type_: ModuleType::Speed,
-                energy_delta_percent: 50.,
-                speed_delta_percent: 20.,
-                productivity_delta_percent: 0.,
-            },
-            Module {
-                name: "speed-module-2".into(),
-                type_: ModuleType::Speed,
-                energy_delta_percent: 60.,
-                speed_delta_percent: 30.,
-                productivity_delta_percent: 0.,
-            },
-            Module {
-                name: "speed-module-3".into(),
-                type_: ModuleType::Speed,
-                energy_delta_percent: 70.,
-                speed_delta_percent: 50.,
-                productivity_delta_percent: 0.,
-            },
-            Module {
-                name: "productivity-module".into(),
-                type_: ModuleType::Productivity,
-                energy_delta_percent: 40.,
-                speed_delta_percent: -5.,
-                productivity_delta_percent: 4.0,
-            },
-            Module {
-                name: "productivity-module-2".into(),
-                type_: ModuleType::Productivity,
-                energy_delta_percent: 60.,
-                speed_delta_percent: -10.,
-                productivity_delta_percent: 6.0,
-            },
-            Module {
-                name: "productivity-module-3".into(),
-                type_: ModuleType::Productivity,
-                energy_delta_percent: 80.,
-                speed_delta_percent: -15.,
-                productivity_delta_percent: 10.0,
-            },
-        ]
+        let modules = game_data
+            .items
+            .values()
+            .filter_map(|item| {
+                let effects = item.module_effects.as_ref()?;
+                // Quality modules are not supported.
+                let type_ = if effects.productivity > 0. {
+                    ModuleType::Productivity
+                } else if effects.speed > 0. {
+                    ModuleType::Speed
+                } else if effects.consumption < 0. {
+                    ModuleType::Efficiency
+                } else {
+                    return None;
+                };
+                Some(Module {
+                    name: item.name.as_str().into(),
+                    type_,
+                    energy_delta_percent: effects.consumption * 100.,
+                    speed_delta_percent: effects.speed * 100.,
+                    productivity_delta_percent: effects.productivity * 100.,
+                    pollution_delta_percent: effects.pollution * 100.,
+                })
+            })
+            .collect_vec();
+        let modules = if modules.is_empty() {
+            default_modules()
+        } else {
+            modules
+        }
         .into_iter()
         .map(|m| (m.name.clone(), m))
         .collect();
@@ -158,11 +303,128 @@ impl Info {
             all_items,
             modules,
             crafters,
+            beacons,
             category_to_crafter,
+            belt_speeds,
+            fluids,
+            unlocked_recipes: BTreeSet::new(),
+            locked_crafters: BTreeSet::new(),
+            planet: None,
         })
     }
 
+    /// Updates `unlocked_recipes` and `locked_crafters` for the researched technologies.
+    pub fn update_unlocked_recipes(&mut self, levels: &ResearchLevels) {
+        let mut unlocked = self
+            .game_data
+            .recipes
+            .values()
+            .filter(|recipe| recipe.enabled)
+            .map(|recipe| recipe.name.clone())
+            .collect::<BTreeSet<_>>();
+        for technology in levels.keys() {
+            let Some(technology) = self.game_data.technologies.get(technology) else {
+                continue;
+            };
+            for effect in &technology.effects {
+                if effect.type_ == "unlock-recipe" {
+                    unlocked.extend(effect.recipe.clone());
+                }
+            }
+        }
+        self.locked_crafters = self
+            .crafters
+            .keys()
+            .filter(|crafter| {
+                let mut producers = self
+                    .game_data
+                    .recipes
+                    .values()
+                    .filter(|recipe| recipe.products.iter().any(|p| p.name == crafter.as_str()))
+                    .peekable();
+                producers.peek().is_some()
+                    && producers.all(|recipe| !unlocked.contains(&recipe.name))
+            })
+            .cloned()
+            .collect();
+        self.unlocked_recipes = unlocked;
+    }
+
+    pub fn is_recipe_unlocked(&self, recipe: &Recipe) -> bool {
+        self.unlocked_recipes.contains(&recipe.name)
+    }
+
+    /// True if surface conditions of the recipe are met on the planet.
+    pub fn is_recipe_possible(&self, recipe: &Recipe) -> bool {
+        let Some(planet) = &self.planet else {
+            return true;
+        };
+        recipe
+            .surface_conditions
+            .iter()
+            .all(|c| c.is_met_on(planet))
+    }
+
+    /// True if surface conditions of the crafter are met on the planet.
+    pub fn is_crafter_possible(&self, crafter: &CrafterName) -> bool {
+        let Some(planet) = &self.planet else {
+            return true;
+        };
+        self.game_data
+            .entities
+            .get(&ItemName::from(crafter.as_str()))
+            .is_none_or(|entity| {
+                entity
+                    .surface_conditions
+                    .iter()
+                    .all(|c| c.is_met_on(planet))
+            })
+    }
+
+    /// True if the item is mined from a resource, but the planet doesn't have that resource.
+    pub fn is_resource_missing_on_planet(&self, item: &ItemName) -> bool {
+        let Some(planet) = &self.planet else {
+            return false;
+        };
+        let mut mined_from = self
+            .game_data
+            .entities
+            .values()
+            .filter(|entity| entity.type_ == "resource")
+            .filter(|entity| {
+                entity
+                    .mineable_properties
+                    .as_ref()
+                    .is_some_and(|m| m.products.iter().any(|p| &p.name == item))
+            })
+            .peekable();
+        mined_from.peek().is_some()
+            && mined_from.all(|entity| !planet.resources.contains(&entity.name))
+    }
+
+    /// True if the recipe passes `Config::recipe_filter` and can be crafted on the planet.
+    pub fn is_recipe_offered(&self, recipe: &Recipe) -> bool {
+        (self.config.recipe_filter == RecipeFilter::All || self.is_recipe_unlocked(recipe))
+            && self.is_recipe_possible(recipe)
+    }
+
+    /// True if the crafter passes `Config::recipe_filter` and can work on the planet.
+    pub fn is_crafter_offered(&self, crafter: &CrafterName) -> bool {
+        (self.config.recipe_filter == RecipeFilter::All || !self.locked_crafters.contains(crafter))
+            && self.is_crafter_possible(crafter)
+    }
+
     pub fn auto_select_crafter(&self, crafters: &[CrafterName]) -> Option<CrafterName> {
+        let offered = crafters
+            .iter()
+            .filter(|c| self.is_crafter_offered(c))
+            .cloned()
+            .collect_vec();
+        let crafters = if offered.is_empty() {
+            crafters
+        } else {
+            &offered
+        };
         if crafters.len() == 1 {
             Some(crafters[0].clone())
         } else if crafters.iter().any(|c| c == &self.config.assembler_type) {
@@ -174,6 +436,130 @@ impl Info {
         }
     }
 
+    pub fn is_fluid(&self, item: &ItemName) -> bool {
+        self.fluids.contains(item)
+    }
+
+    /// Belt selected in config, or the fastest available belt if the config doesn't name
+    /// a known belt. An unknown belt is reported when the config is loaded.
+    pub fn belt(&self) -> Option<(Speed, &ItemName)> {
+        self.config
+            .belt_type
+            .as_ref()
+            .and_then(|name| self.belt_speeds.iter().find(|(_, belt)| belt == name))
+            .or_else(|| self.belt_speeds.last())
+            .map(|(speed, belt)| (*speed, belt))
+    }
+
+    fn belt_speed(&self, belt: &ItemName) -> Option<Speed> {
+        self.belt_speeds
+            .iter()
+            .find(|(_, b)| b == belt)
+            .map(|(speed, _)| *speed)
+    }
+
+    /// Unit of rates of `item`: the configured unit, except that fluids are per second
+    /// if rates are in belts.
+    pub fn rate_unit(&self, item: &ItemName) -> RateUnit {
+        match &self.config.rate_unit {
+            RateUnit::Belt(_) if self.is_fluid(item) => RateUnit::PerSecond,
+            unit => unit.clone(),
+        }
+    }
+
+    /// Formats a rate of `item` in its unit, e.g. "900/min" or "0.5 × transport-belt".
+    pub fn format_speed(&self, item: &ItemName, speed: Speed) -> String {
+        let unit = self.rate_unit(item);
+        if let Some(factor) = unit.per_second_factor() {
+            return format!("{}{}", rf(f64::from(speed) * factor), unit.suffix());
+        }
+        if let RateUnit::Belt(belt) = &unit {
+            if let Some(belt_speed) = self.belt_speed(belt) {
+                return format!("{} × {belt}", rf(speed / belt_speed));
+            }
+        }
+        speed.to_string()
+    }
+
+    /// Rate of `item` as text for an input field: a number in the unit of the item without
+    /// rounding, so that `Info::parse_speed` returns exactly the same rate.
+    pub fn speed_input_text(&self, item: &ItemName, speed: Speed) -> String {
+        let unit = self.rate_unit(item);
+        if let Some(factor) = unit.per_second_factor() {
+            return (f64::from(speed) * factor).to_string();
+        }
+        if let RateUnit::Belt(belt) = &unit {
+            if let Some(belt_speed) = self.belt_speed(belt) {
+                return (speed / belt_speed).to_string();
+            }
+        }
+        format!("{}/s", f64::from(speed))
+    }
+
+    /// Parses a rate of `item` like "15", "15/s", "900/min", "2 yellow belts", "0.5 belt"
+    /// or "1 fast-transport-belt". A plain number is in the unit of the item (see `Info::rate_unit`).
+    /// "belt" alone means the belt from `Info::belt`.
+    pub fn parse_speed(&self, item: &ItemName, text: &str) -> anyhow::Result<Speed> {
+        let text = text.trim();
+        let number_end = text
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == 'e'))
+            .unwrap_or(text.len());
+        let (number, unit) = text.split_at(number_end);
+        let number: f64 = number
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid rate: {text:?}"))?;
+        let unit = unit.trim().trim_start_matches(['×', '*', 'x']).trim();
+        let per_second = match unit {
+            "" => {
+                let unit = self.rate_unit(item);
+                return if let Some(factor) = unit.per_second_factor() {
+                    Ok(Speed::from(number / factor))
+                } else {
+                    self.parse_speed(item, &format!("{number} {}", unit.suffix()))
+                };
+            }
+            "/s" | "/sec" => 1.0,
+            "/m" | "/min" => 60.0,
+            "/h" | "/hour" => 3600.0,
+            _ => {
+                let belt = self
+                    .parse_belt(unit)
+                    .with_context(|| format!("unknown rate unit: {unit:?}"))?;
+                if self.is_fluid(item) {
+                    bail!("{item} is a fluid and can't be measured in belts");
+                }
+                return Ok(belt * number);
+            }
+        };
+        Ok(Speed::from(number / per_second))
+    }
+
+    fn parse_belt(&self, text: &str) -> Option<Speed> {
+        let text = text.strip_suffix('s').unwrap_or(text);
+        if let Some(speed) = self.belt_speed(&text.into()) {
+            return Some(speed);
+        }
+        let text = text.trim_end_matches("belt").trim();
+        let name = match text {
+            "" => return self.belt().map(|(speed, _)| speed),
+            "yellow" => "transport-belt",
+            "red" => "fast-transport-belt",
+            "blue" => "express-transport-belt",
+            "green" | "turbo" => "turbo-transport-belt",
+            _ => return None,
+        };
+        self.belt_speed(&name.into())
+    }
+
+    pub fn beacon(&self, name: Option<&BeaconName>) -> anyhow::Result<&BeaconType> {
+        let default = BeaconName::default_name();
+        let name = name.unwrap_or(&default);
+        self.beacons
+            .get(name)
+            .with_context(|| format!("invalid beacon name: {name:?}"))
+    }
+
     pub fn module(&self, name: &ModuleName) -> anyhow::Result<&Module> {
         self.modules
             .get(name)
@@ -1,15 +1,31 @@
 mod analyze;
+mod bom;
 mod config;
+mod cost;
 mod editor;
+mod export;
 mod flowchart;
 mod game_data;
 mod info;
 mod machine;
+mod optimizer;
+mod power;
 mod primitives;
+mod rational;
+mod research;
+mod rocket;
+mod sankey;
+mod sensitivity;
 mod snippet;
+mod spoilage;
+mod train;
 pub mod ui;
 
-pub use crate::info::Info;
+pub use crate::{
+    editor::Editor,
+    export::{exporter, exporters, Exporter},
+    info::Info,
+};
 use {machine::Module, std::collections::BTreeMap, tracing::warn};
 
 /// Round float to second decimal digit.
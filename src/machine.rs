@@ -1,12 +1,18 @@
 use {
     crate::{
-        game_data::{Ingredient, Product, Recipe},
+        editor::BUILDING_COUNT_EPSILON,
+        game_data::{Effects, Ingredient, Product, Recipe},
+        info::Info,
         module_counts,
-        primitives::{Amount, CrafterName, ItemName, ModuleName, RecipeCategory, Speed},
+        primitives::{
+            Amount, BeaconName, CrafterName, ItemName, ModuleName, RecipeCategory, Speed,
+        },
+        research::ResearchBonus,
         rf,
     },
     itertools::Itertools,
     serde::{Deserialize, Serialize},
+    std::collections::BTreeMap,
 };
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -16,6 +22,9 @@ pub struct Crafter {
     pub crafting_speed: f64,
     #[serde(default)] // only for compatibility
     pub module_inventory_size: u64,
+    /// Emissions at full power by pollutant (e.g. "pollution", "spores"), per minute.
+    #[serde(default)] // only for compatibility
+    pub emissions_per_minute: BTreeMap<String, f64>,
 }
 
 impl Crafter {
@@ -34,6 +43,7 @@ impl Crafter {
 pub enum ModuleType {
     Speed,
     Productivity,
+    Efficiency,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -43,14 +53,119 @@ pub struct Module {
     pub energy_delta_percent: f64,
     pub speed_delta_percent: f64,
     pub productivity_delta_percent: f64,
+    #[serde(default)] // only for compatibility
+    pub pollution_delta_percent: f64,
+}
+
+impl Module {
+    pub fn is_allowed_by(&self, effects: &Effects) -> bool {
+        (self.energy_delta_percent == 0. || effects.consumption)
+            && (self.speed_delta_percent == 0. || effects.speed)
+            && (self.productivity_delta_percent == 0. || effects.productivity)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BeaconType {
+    pub name: BeaconName,
+    pub distribution_effectivity: f64,
+    pub distribution_effectivity_bonus_per_quality_level: f64,
+    /// Transmission strength multiplier by the number of beacons affecting a machine (starting at 1).
+    /// The last value is used for larger numbers of beacons.
+    pub profile: Vec<f64>,
+    /// If true, only beacons of the same type are counted for `profile`.
+    pub count_same_type_only: bool,
+    pub module_inventory_size: u64,
+    pub energy_usage: f64,
+    pub allowed_effects: Effects,
+}
+
+impl BeaconType {
+    /// Beacon from the base game, used if game data doesn't contain beacons.
+    pub fn vanilla() -> Self {
+        BeaconType {
+            name: BeaconName::default_name(),
+            distribution_effectivity: 1.5,
+            distribution_effectivity_bonus_per_quality_level: 0.2,
+            profile: (1..=100).map(|n| 1.0 / (n as f64).sqrt()).collect(),
+            count_same_type_only: false,
+            module_inventory_size: 2,
+            energy_usage: 8000.0,
+            allowed_effects: Effects {
+                consumption: true,
+                speed: true,
+                productivity: false,
+                pollution: true,
+                quality: false,
+            },
+        }
+    }
+
+    pub fn distribution_effectivity(&self, quality: u32) -> f64 {
+        self.distribution_effectivity
+            + quality as f64 * self.distribution_effectivity_bonus_per_quality_level
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
-#[serde(transparent)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Beacon {
+    pub type_: BeaconType,
+    pub quality: u32,
     pub modules: Vec<Module>,
 }
 
+impl Beacon {
+    /// Type, modules and quality, e.g. "beacon(2 × speed-module-3, quality 1)".
+    pub fn text(&self) -> String {
+        let modules = module_counts(&self.modules)
+            .into_iter()
+            .map(|(name, count)| format!("{count} × {name}"))
+            .join(",");
+        let quality = if self.quality > 0 {
+            format!(", quality {}", self.quality)
+        } else {
+            String::new()
+        };
+        format!("{}({modules}{quality})", self.type_.name)
+    }
+}
+
+/// Formats emissions per minute, e.g. "12.5/min pollution + 3/min spores".
+pub fn format_emissions(emissions: &BTreeMap<String, f64>) -> String {
+    emissions
+        .iter()
+        .map(|(pollutant, value)| format!("{}/min {pollutant}", rf(*value)))
+        .join(" + ")
+}
+
+// Speed, energy consumption and pollution can't be reduced by more than 80%.
+const MIN_SPEED_MULTIPLIER: f64 = 0.2;
+const MIN_CONSUMPTION_MULTIPLIER: f64 = 0.2;
+const MIN_POLLUTION_MULTIPLIER: f64 = 0.2;
+
+/// A rule of the game that limits the module effects of a machine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EffectLimit {
+    MinimumSpeed,
+    MinimumConsumption,
+    /// Maximum productivity bonus of the recipe, e.g. 3.0 for +300%.
+    MaximumProductivity(f64),
+}
+
+impl EffectLimit {
+    pub fn description(&self) -> String {
+        match self {
+            EffectLimit::MinimumSpeed => "speed is limited to 20% minimum".to_string(),
+            EffectLimit::MinimumConsumption => {
+                "energy consumption is limited to 20% minimum".to_string()
+            }
+            EffectLimit::MaximumProductivity(max) => {
+                format!("productivity is capped at +{}%", rf(max * 100.0))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Machine {
     pub crafter: Crafter,
@@ -58,6 +173,8 @@ pub struct Machine {
     pub modules: Vec<Module>,
     pub beacons: Vec<Beacon>,
     pub recipe: Recipe,
+    #[serde(default)]
+    pub research: ResearchBonus,
 }
 
 #[derive(Debug, Clone)]
@@ -74,6 +191,7 @@ impl Machine {
                 energy_usage: 0.0,
                 crafting_speed: 1.0,
                 module_inventory_size: 0,
+                emissions_per_minute: BTreeMap::new(),
             },
             crafter_count: 1.0,
             recipe: Recipe {
@@ -83,6 +201,7 @@ impl Machine {
                 ingredients: Vec::new(),
                 products: vec![Product {
                     amount: Amount::ONE,
+                    percent_spoiled: 0.0,
                     name: item.clone(),
                     type_: String::new(),
                     extra_count_fraction: 0.0,
@@ -96,9 +215,13 @@ impl Machine {
                 order: String::new(),
                 productivity_bonus: 0.0,
                 allowed_effects: Default::default(),
+                maximum_productivity: Recipe::DEFAULT_MAXIMUM_PRODUCTIVITY,
+                surface_conditions: Vec::new(),
+                emissions_multiplier: 1.0,
             },
             modules: Vec::new(),
             beacons: Vec::new(),
+            research: ResearchBonus::default(),
         }
     }
 
@@ -109,6 +232,7 @@ impl Machine {
                 energy_usage: 0.0,
                 crafting_speed: 1.0,
                 module_inventory_size: 0,
+                emissions_per_minute: BTreeMap::new(),
             },
             crafter_count: 1.0,
             recipe: Recipe {
@@ -127,42 +251,147 @@ impl Machine {
                 order: String::new(),
                 productivity_bonus: 0.0,
                 allowed_effects: Default::default(),
+                maximum_productivity: Recipe::DEFAULT_MAXIMUM_PRODUCTIVITY,
+                surface_conditions: Vec::new(),
+                emissions_multiplier: 1.0,
             },
             modules: Vec::new(),
             beacons: Vec::new(),
+            research: ResearchBonus::default(),
         }
     }
 
-    pub fn beacon_transmission_strength(&self) -> f64 {
-        if self.beacons.is_empty() {
-            0.0
+    /// Crafter count rounded up to whole buildings.
+    pub fn buildings(&self) -> u64 {
+        (self.crafter_count - BUILDING_COUNT_EPSILON)
+            .max(0.0)
+            .ceil() as u64
+    }
+
+    /// Multiplier applied to module effects of each beacon affecting this machine.
+    pub fn beacon_transmission_strength(&self, beacon: &Beacon) -> f64 {
+        let count = if beacon.type_.count_same_type_only {
+            self.beacons
+                .iter()
+                .filter(|b| b.type_.name == beacon.type_.name)
+                .count()
         } else {
-            1.5 / (self.beacons.len() as f64).sqrt()
-        }
+            self.beacons.len()
+        };
+        let profile = beacon
+            .type_
+            .profile
+            .get(count.saturating_sub(1))
+            .or(beacon.type_.profile.last())
+            .copied()
+            .unwrap_or(1.0);
+        beacon.type_.distribution_effectivity(beacon.quality) * profile
     }
 
-    // Not including productivity.
-    pub fn crafts_per_second(&self) -> Speed {
-        let beacon_transmission_strength = self.beacon_transmission_strength();
-        let module_speed_percents: f64 = self
-            .modules
-            .iter()
-            .map(|module| module.speed_delta_percent)
-            .sum();
-        let beacon_speed_percents: f64 = self
+    /// Sum of `effect` over modules in this machine and its beacons, in percent.
+    fn total_module_effect(&self, effect: impl Fn(&Module) -> f64) -> f64 {
+        let module_percents: f64 = self.modules.iter().map(&effect).sum();
+        let beacon_percents: f64 = self
             .beacons
             .iter()
-            .flat_map(|b| &b.modules)
-            .map(|module| module.speed_delta_percent)
+            .map(|beacon| {
+                self.beacon_transmission_strength(beacon)
+                    * beacon.modules.iter().map(&effect).sum::<f64>()
+            })
             .sum();
-        let speed_percents =
-            100. + module_speed_percents + beacon_transmission_strength * beacon_speed_percents;
+        module_percents + beacon_percents
+    }
 
-        ((speed_percents / 100.) * self.crafter.crafting_speed * self.crafter_count
+    fn uncapped_speed_multiplier(&self) -> f64 {
+        (100. + self.total_module_effect(|module| module.speed_delta_percent)) / 100.
+    }
+
+    /// Crafting speed multiplier from modules and beacons, e.g. 1.5 for +50%.
+    pub fn speed_multiplier(&self) -> f64 {
+        self.uncapped_speed_multiplier().max(MIN_SPEED_MULTIPLIER)
+    }
+
+    // Not including productivity.
+    pub fn crafts_per_second(&self) -> Speed {
+        (self.speed_multiplier() * self.crafter.crafting_speed * self.crafter_count
             / self.recipe.energy)
             .into()
     }
 
+    fn uncapped_consumption_multiplier(&self) -> f64 {
+        (100. + self.total_module_effect(|module| module.energy_delta_percent)) / 100.
+    }
+
+    /// Energy consumption multiplier from modules and beacons, e.g. 1.5 for +50%.
+    pub fn consumption_multiplier(&self) -> f64 {
+        self.uncapped_consumption_multiplier()
+            .max(MIN_CONSUMPTION_MULTIPLIER)
+    }
+
+    fn uncapped_productivity(&self) -> f64 {
+        let recipe_bonus = if self.research.replaces_recipe_bonus {
+            0.0
+        } else {
+            self.recipe.productivity_bonus
+        };
+        (self.total_module_effect(|module| module.productivity_delta_percent) + recipe_bonus) / 100.
+            + self.research.productivity
+    }
+
+    /// Productivity bonus from modules, the recipe and research, e.g. 0.5 for +50%.
+    pub fn productivity(&self) -> f64 {
+        self.uncapped_productivity()
+            .min(self.recipe.maximum_productivity)
+    }
+
+    /// Limits of the game that are reached with the current modules and beacons,
+    /// so some module effects are wasted.
+    pub fn effect_limits(&self) -> Vec<EffectLimit> {
+        let mut limits = Vec::new();
+        if self.uncapped_speed_multiplier() < MIN_SPEED_MULTIPLIER {
+            limits.push(EffectLimit::MinimumSpeed);
+        }
+        if self.uncapped_consumption_multiplier() < MIN_CONSUMPTION_MULTIPLIER {
+            limits.push(EffectLimit::MinimumConsumption);
+        }
+        if self.uncapped_productivity() > self.recipe.maximum_productivity {
+            limits.push(EffectLimit::MaximumProductivity(
+                self.recipe.maximum_productivity,
+            ));
+        }
+        limits
+    }
+
+    /// Pollution multiplier from modules and beacons, e.g. 1.1 for +10%.
+    pub fn pollution_multiplier(&self) -> f64 {
+        ((100. + self.total_module_effect(|module| module.pollution_delta_percent)) / 100.)
+            .max(MIN_POLLUTION_MULTIPLIER)
+    }
+
+    /// Emissions of all crafters of this machine by pollutant (e.g. "pollution", "spores"),
+    /// per minute. Emissions grow with energy consumption and the pollution effect of modules.
+    pub fn emissions(&self) -> BTreeMap<String, f64> {
+        let multiplier = self.consumption_multiplier()
+            * self.pollution_multiplier()
+            * self.recipe.emissions_multiplier
+            * self.crafter_count;
+        self.crafter
+            .emissions_per_minute
+            .iter()
+            .map(|(pollutant, emissions)| (pollutant.clone(), emissions * multiplier))
+            .collect()
+    }
+
+    /// Power consumed by all crafters of this machine and their beacons, in watts.
+    /// Beacons are assumed not to be shared between crafters.
+    pub fn power_usage(&self) -> f64 {
+        // energy_usage is in joules per tick.
+        let beacons_energy_usage: f64 = self.beacons.iter().map(|b| b.type_.energy_usage).sum();
+        (self.crafter.energy_usage * self.consumption_multiplier() + beacons_energy_usage)
+            * 60.
+            * self.crafter_count
+    }
+
     pub fn input_speeds(&self) -> impl Iterator<Item = ItemSpeed> + '_ {
         let crafts_per_second = self.crafts_per_second();
         self.recipe.ingredients.iter().map(move |ing| ItemSpeed {
@@ -171,30 +400,22 @@ impl Machine {
         })
     }
 
+    /// Expected output speeds. Productivity only applies to the part of a product
+    /// that is not `ignored_by_productivity` (e.g. catalysts in Kovarex enrichment),
+    /// and products with `probability` below 1 are averaged.
     pub fn output_speeds(&self) -> impl Iterator<Item = ItemSpeed> + '_ {
-        let crafts_per_second = self.crafts_per_second();
-        let beacon_transmission_strength = self.beacon_transmission_strength();
-        let module_prod_percents: f64 = self
-            .modules
-            .iter()
-            .map(|module| module.productivity_delta_percent)
-            .sum();
-        let beacon_prod_percents: f64 = self
-            .beacons
-            .iter()
-            .flat_map(|b| &b.modules)
-            .map(|module| module.productivity_delta_percent)
-            .sum();
-        let prod_percents = 100.
-            + module_prod_percents
-            + beacon_transmission_strength * beacon_prod_percents
-            + self.recipe.productivity_bonus;
-
-        let output_speed = (prod_percents / 100.) * crafts_per_second;
+        let crafts_per_second = f64::from(self.crafts_per_second());
+        let productivity = self.productivity();
 
-        self.recipe.products.iter().map(move |product| ItemSpeed {
-            item: product.name.clone(),
-            speed: output_speed * (product.amount - product.ignored_by_productivity),
+        self.recipe.products.iter().map(move |product| {
+            let amount = f64::from(product.amount);
+            let affected = (amount - f64::from(product.ignored_by_productivity)).max(0.0);
+            let per_craft = product.probability
+                * (amount + product.extra_count_fraction + productivity * affected);
+            ItemSpeed {
+                item: product.name.clone(),
+                speed: (crafts_per_second * per_craft).into(),
+            }
         })
     }
 
@@ -202,15 +423,37 @@ impl Machine {
         self.input_speeds().chain(self.output_speeds())
     }
 
-    pub fn description(&self) -> String {
+    /// Like `item_speeds`, but an item that is both consumed and produced (a catalyst)
+    /// has a single entry with the net speed.
+    pub fn net_item_speeds(&self) -> Vec<ItemSpeed> {
+        let mut speeds = Vec::<ItemSpeed>::new();
+        for item_speed in self.item_speeds() {
+            if let Some(existing) = speeds.iter_mut().find(|s| s.item == item_speed.item) {
+                existing.speed += item_speed.speed;
+            } else {
+                speeds.push(item_speed);
+            }
+        }
+        speeds
+    }
+
+    /// `net_item_speeds` of a single crafter, so that the direction of each item
+    /// is known even if the solved count is zero.
+    pub fn net_item_speeds_per_crafter(&self) -> Vec<ItemSpeed> {
+        let mut machine = self.clone();
+        machine.crafter_count = 1.0;
+        machine.net_item_speeds()
+    }
+
+    pub fn description(&self, info: &Info) -> String {
         let inputs = self
             .input_speeds()
-            .map(|ing| format!("{} {}", ing.speed, ing.item))
+            .map(|ing| format!("{} {}", info.format_speed(&ing.item, ing.speed), ing.item))
             .join(" + ");
 
         let outputs = self
             .output_speeds()
-            .map(|ing| format!("{} {}", ing.speed, ing.item))
+            .map(|ing| format!("{} {}", info.format_speed(&ing.item, ing.speed), ing.item))
             .join(" + ");
 
         let inputs = if inputs.is_empty() {
@@ -240,22 +483,25 @@ impl Machine {
             return String::new();
         }
         if self.beacons.iter().all_equal() {
-            let modules = module_counts(&self.beacons[0].modules)
-                .into_iter()
-                .map(|(name, count)| format!("{count} × {name}"))
-                .join(",");
-            format!("{} × beacon({})", self.beacons.len(), modules)
+            format!("{} × {}", self.beacons.len(), self.beacons[0].text())
         } else {
-            self.beacons
-                .iter()
-                .map(|beacon| {
-                    let modules = module_counts(&beacon.modules)
-                        .into_iter()
-                        .map(|(name, count)| format!("{count} × {name}"))
-                        .join(",");
-                    format!("beacon({})", modules)
-                })
-                .join("\n")
+            self.beacons.iter().map(Beacon::text).join("\n")
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buildings_ignore_float_noise() {
+        let mut machine = Machine::new_source(&ItemName::from("iron-plate"));
+        machine.crafter_count = 60.0000000001;
+        assert_eq!(machine.buildings(), 60);
+        machine.crafter_count = 60.01;
+        assert_eq!(machine.buildings(), 61);
+        machine.crafter_count = 0.0;
+        assert_eq!(machine.buildings(), 0);
+    }
+}
@@ -0,0 +1,305 @@
+use {
+    crate::{
+        cost::{item_costs, ItemCost},
+        editor::Editor,
+        machine::{Beacon, BeaconType, Machine, Module},
+        primitives::ItemName,
+    },
+    itertools::Itertools,
+    std::collections::BTreeMap,
+    tracing::subscriber::NoSubscriber,
+};
+
+// Crafter and beacon sizes are not in game data, most of them are 3x3.
+const BUILDING_AREA: f64 = 9.0;
+// Candidates of every machine that are solved with the whole snippet, per shortlist.
+const SHORTLIST_LEN: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    BuildingCount,
+    Power,
+    RawInput,
+    Footprint,
+}
+
+impl Objective {
+    pub const ALL: [Objective; 4] = [
+        Objective::BuildingCount,
+        Objective::Power,
+        Objective::RawInput,
+        Objective::Footprint,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Objective::BuildingCount => "Building count",
+            Objective::Power => "Power",
+            Objective::RawInput => "Raw input",
+            Objective::Footprint => "Footprint",
+        }
+    }
+}
+
+/// Totals of a solved snippet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    /// Crafters, without sources and sinks.
+    pub buildings: f64,
+    /// Watts.
+    pub power: f64,
+    /// Raw resources needed for the inputs of the snippet, items/s (see `cost::item_costs`).
+    pub raw_input: f64,
+    /// Tiles of crafters and their beacons. Beacons are not shared between crafters,
+    /// same as in power usage and the bill of materials.
+    pub footprint: f64,
+}
+
+impl Score {
+    fn of(editor: &Editor, costs: &BTreeMap<ItemName, ItemCost>) -> Self {
+        let mut score = Score {
+            buildings: 0.0,
+            power: editor.power_usage(),
+            raw_input: 0.0,
+            footprint: 0.0,
+        };
+        for editor_machine in editor.machines() {
+            let machine = editor_machine.machine();
+            if machine.crafter.is_source() {
+                for output in machine.output_speeds() {
+                    // Items without known recipes are raw.
+                    let raw = costs.get(&output.item).map_or(1.0, ItemCost::total_raw);
+                    score.raw_input += f64::from(output.speed) * raw;
+                }
+            } else if !machine.crafter.is_sink() {
+                score.buildings += machine.crafter_count;
+                score.footprint += footprint(machine);
+            }
+        }
+        score
+    }
+
+    pub fn value(&self, objective: Objective) -> f64 {
+        match objective {
+            Objective::BuildingCount => self.buildings,
+            Objective::Power => self.power,
+            Objective::RawInput => self.raw_input,
+            Objective::Footprint => self.footprint,
+        }
+    }
+}
+
+fn footprint(machine: &Machine) -> f64 {
+    machine.crafter_count * BUILDING_AREA * (1.0 + machine.beacons.len() as f64)
+}
+
+/// Estimate of `objective` for a single machine, used to shortlist its candidates before
+/// solving the whole snippet. Raw input is estimated by the direct ingredients of the machine.
+fn local_value(machine: &Machine, objective: Objective) -> f64 {
+    match objective {
+        Objective::BuildingCount => machine.crafter_count,
+        Objective::Power => machine.power_usage(),
+        Objective::RawInput => ingredients(machine),
+        Objective::Footprint => footprint(machine),
+    }
+}
+
+fn ingredients(machine: &Machine) -> f64 {
+    -machine
+        .input_speeds()
+        .map(|i| f64::from(i.speed))
+        .sum::<f64>()
+}
+
+// Modules and beacons of a machine.
+type Configuration = (Vec<Module>, Vec<Beacon>);
+
+#[derive(Debug, Clone)]
+pub struct MachineOptimization {
+    pub machine_index: usize,
+    pub modules: Vec<Module>,
+    pub beacons: Vec<Beacon>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Optimization {
+    pub objective: Objective,
+    /// Only machines whose configuration changes.
+    pub machines: Vec<MachineOptimization>,
+    /// Totals of the snippet before and after applying the optimization.
+    pub before: Score,
+    pub after: Score,
+}
+
+/// Effective crafts per second of one building, including productivity.
+/// All outputs of a machine scale with it, so keeping it constant keeps outputs constant.
+fn effective_rate(machine: &Machine) -> f64 {
+    let mut machine = machine.clone();
+    machine.crafter_count = 1.0;
+    let output: f64 = machine.output_speeds().map(|o| f64::from(o.speed)).sum();
+    if output > 0.0 {
+        output
+    } else {
+        f64::from(machine.crafts_per_second())
+    }
+}
+
+/// Chooses modules and beacons for every crafter of the snippet so that `objective` of the
+/// whole snippet is minimal.
+///
+/// Crafters are optimized one by one. Candidates of a crafter are shortlisted by their local
+/// value with the crafter output fixed, then every shortlisted candidate is scored on the
+/// solved snippet, so e.g. productivity is credited with the ingredients that upstream
+/// crafters don't need to make. The current configuration is always a candidate, so the
+/// result is never worse than the snippet.
+///
+/// `modules` are candidates for module slots, `beacon_module` fills every beacon of `beacon_type`.
+pub fn optimize(
+    editor: &Editor,
+    objective: Objective,
+    modules: &[Module],
+    beacon_type: &BeaconType,
+    beacon_module: Option<&Module>,
+    max_beacons: usize,
+) -> Optimization {
+    // Trial solves would repeat the warnings of the snippet for every candidate.
+    tracing::subscriber::with_default(NoSubscriber::default(), || {
+        optimize_quietly(
+            editor,
+            objective,
+            modules,
+            beacon_type,
+            beacon_module,
+            max_beacons,
+        )
+    })
+}
+
+fn optimize_quietly(
+    editor: &Editor,
+    objective: Objective,
+    modules: &[Module],
+    beacon_type: &BeaconType,
+    beacon_module: Option<&Module>,
+    max_beacons: usize,
+) -> Optimization {
+    let costs = item_costs(editor, &editor.added_items());
+    let before = Score::of(editor, &costs);
+    let mut trial = editor.trial();
+    let mut machines = Vec::new();
+    for machine_index in 0..editor.machines().len() {
+        let machine = trial.machines()[machine_index].machine().clone();
+        if machine.crafter.is_source_or_sink() || machine.crafter_count <= 0.0 {
+            continue;
+        }
+        let target_rate = effective_rate(&machine) * machine.crafter_count;
+
+        let allowed_modules = modules
+            .iter()
+            .filter(|m| m.is_allowed_by(&machine.recipe.allowed_effects))
+            .collect_vec();
+        let beacon = beacon_module
+            .filter(|m| {
+                m.is_allowed_by(&beacon_type.allowed_effects)
+                    && m.is_allowed_by(&machine.recipe.allowed_effects)
+            })
+            .map(|module| Beacon {
+                type_: beacon_type.clone(),
+                quality: 0,
+                modules: vec![module.clone(); beacon_type.module_inventory_size as usize],
+            });
+        let max_beacons = if beacon.is_some() { max_beacons } else { 0 };
+
+        let mut candidates = Vec::new();
+        for num_modules in 0..=machine.crafter.module_inventory_size as usize {
+            for candidate_modules in allowed_modules
+                .iter()
+                .copied()
+                .cloned()
+                .combinations_with_replacement(num_modules)
+            {
+                for num_beacons in 0..=max_beacons {
+                    let mut candidate = machine.clone();
+                    candidate.modules = candidate_modules.clone();
+                    candidate.beacons = beacon.iter().cloned().cycle().take(num_beacons).collect();
+                    let rate = effective_rate(&candidate);
+                    if rate <= 0.0 {
+                        continue;
+                    }
+                    candidate.crafter_count = target_rate / rate;
+                    candidates.push(candidate);
+                }
+            }
+        }
+        // Ingredients are shortlisted for every objective because productivity pays off
+        // in the upstream crafters.
+        let by_objective = candidates
+            .iter()
+            .sorted_by(|a, b| local_value(a, objective).total_cmp(&local_value(b, objective)))
+            .take(SHORTLIST_LEN);
+        let by_ingredients = candidates
+            .iter()
+            .sorted_by(|a, b| ingredients(a).total_cmp(&ingredients(b)))
+            .take(SHORTLIST_LEN);
+        let mut shortlist: Vec<Configuration> =
+            vec![(machine.modules.clone(), machine.beacons.clone())];
+        for candidate in by_objective.chain(by_ingredients) {
+            let configuration = (candidate.modules.clone(), candidate.beacons.clone());
+            if !shortlist.contains(&configuration) {
+                shortlist.push(configuration);
+            }
+        }
+
+        let mut best: Option<(Configuration, f64)> = None;
+        for (candidate_modules, candidate_beacons) in shortlist {
+            if trial
+                .try_configuration(
+                    machine_index,
+                    candidate_modules.clone(),
+                    candidate_beacons.clone(),
+                )
+                .is_err()
+            {
+                continue;
+            }
+            let value = Score::of(&trial, &costs).value(objective);
+            let is_better = best
+                .as_ref()
+                .is_none_or(|((modules, beacons), best_value)| {
+                    // Prefer simpler configurations when the objective is the same.
+                    value < best_value - best_value.abs() * 1e-9
+                        || (value <= best_value + best_value.abs() * 1e-9
+                            && candidate_modules.len() + candidate_beacons.len()
+                                < modules.len() + beacons.len())
+                });
+            if is_better {
+                best = Some(((candidate_modules, candidate_beacons), value));
+            }
+        }
+        let Some(((best_modules, best_beacons), _)) = best else {
+            continue;
+        };
+        // Later machines are optimized with the best configuration of this one.
+        if trial
+            .try_configuration(machine_index, best_modules.clone(), best_beacons.clone())
+            .is_err()
+        {
+            continue;
+        }
+        let original = editor.machines()[machine_index].machine();
+        if best_modules != original.modules || best_beacons != original.beacons {
+            machines.push(MachineOptimization {
+                machine_index,
+                modules: best_modules,
+                beacons: best_beacons,
+            });
+        }
+    }
+    let after = Score::of(&trial, &costs);
+    Optimization {
+        objective,
+        machines,
+        before,
+        after,
+    }
+}
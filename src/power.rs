@@ -0,0 +1,251 @@
+use {
+    crate::{game_data::Entity, info::Info, primitives::ItemName, rf},
+    anyhow::{ensure, Context},
+    serde::{Deserialize, Serialize},
+};
+
+const BOILER: &str = "boiler";
+const STEAM_ENGINE: &str = "steam-engine";
+const OFFSHORE_PUMP: &str = "offshore-pump";
+const SOLAR_PANEL: &str = "solar-panel";
+const ACCUMULATOR: &str = "accumulator";
+const NUCLEAR_REACTOR: &str = "nuclear-reactor";
+const HEAT_EXCHANGER: &str = "heat-exchanger";
+const STEAM_TURBINE: &str = "steam-turbine";
+
+// Parts of the day-night cycle. Solar panels work at full power during the day,
+// output changes linearly during dusk and dawn, and there is no output at night.
+const DAY: f64 = 0.5;
+const DUSK: f64 = 0.2;
+const NIGHT: f64 = 0.1;
+const DAWN: f64 = 0.2;
+
+// Length of the day-night cycle on Nauvis, in ticks. Used if no planet is selected.
+const DEFAULT_DAY_NIGHT_CYCLE: f64 = 25_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PowerSource {
+    /// Boilers, steam engines and offshore pumps.
+    Steam,
+    /// Solar panels and accumulators.
+    Solar,
+    /// Nuclear reactors, heat exchangers, steam turbines and offshore pumps.
+    Nuclear,
+}
+
+impl PowerSource {
+    pub const ALL: [PowerSource; 3] =
+        [PowerSource::Steam, PowerSource::Solar, PowerSource::Nuclear];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerSource::Steam => "Steam",
+            PowerSource::Solar => "Solar",
+            PowerSource::Nuclear => "Nuclear",
+        }
+    }
+}
+
+/// Buildings that generate the required power.
+#[derive(Debug, Clone)]
+pub struct PowerPlan {
+    pub source: PowerSource,
+    /// Required power, in watts.
+    pub power: f64,
+    pub buildings: Vec<(ItemName, u64)>,
+    /// Details of the plan, e.g. water consumption.
+    pub notes: Vec<String>,
+}
+
+impl PowerPlan {
+    pub fn buildings_text(&self) -> String {
+        self.buildings
+            .iter()
+            .map(|(name, count)| format!("{count} × {name}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+pub fn format_power(watts: f64) -> String {
+    if watts >= 1e9 {
+        format!("{} GW", rf(watts / 1e9))
+    } else if watts >= 1e6 {
+        format!("{} MW", rf(watts / 1e6))
+    } else {
+        format!("{} kW", rf(watts / 1e3))
+    }
+}
+
+fn entity<'a>(info: &'a Info, name: &str) -> anyhow::Result<&'a Entity> {
+    info.game_data
+        .entities
+        .get(&ItemName::from(name))
+        .with_context(|| {
+            format!("{name} is missing in game data, export it again with export_game_data.lua")
+        })
+}
+
+// Entity values are per tick; results are per second.
+fn per_second(value: Option<f64>, entity: &Entity, property: &str) -> anyhow::Result<f64> {
+    let value = value.with_context(|| format!("missing {property} for {}", entity.name))?;
+    ensure!(value > 0.0, "invalid {property} for {}", entity.name);
+    Ok(value * 60.0)
+}
+
+fn count(value: f64) -> u64 {
+    // Avoid an extra building because of float errors.
+    (value - 1e-9).ceil().max(0.0) as u64
+}
+
+/// Computes buildings that generate `power` watts.
+pub fn plan(info: &Info, source: PowerSource, power: f64) -> anyhow::Result<PowerPlan> {
+    ensure!(
+        power.is_finite() && power > 0.0,
+        "required power must be positive"
+    );
+    let mut plan = PowerPlan {
+        source,
+        power,
+        buildings: Vec::new(),
+        notes: Vec::new(),
+    };
+    match source {
+        PowerSource::Steam => plan_steam(info, &mut plan)?,
+        PowerSource::Solar => plan_solar(info, &mut plan)?,
+        PowerSource::Nuclear => plan_nuclear(info, &mut plan)?,
+    }
+    Ok(plan)
+}
+
+fn add_offshore_pumps(info: &Info, plan: &mut PowerPlan, water: f64) -> anyhow::Result<()> {
+    let pump = entity(info, OFFSHORE_PUMP)?;
+    let pumping_speed = per_second(pump.pumping_speed, pump, "pumping_speed")?;
+    plan.buildings
+        .push((pump.name.clone(), count(water / pumping_speed)));
+    plan.notes.push(format!("Water: {}/s", rf(water)));
+    Ok(())
+}
+
+fn plan_steam(info: &Info, plan: &mut PowerPlan) -> anyhow::Result<()> {
+    let boiler = entity(info, BOILER)?;
+    let engine = entity(info, STEAM_ENGINE)?;
+    let boiler_power = per_second(boiler.max_energy_usage, boiler, "max_energy_usage")?;
+    let engine_power = per_second(
+        engine.max_energy_production,
+        engine,
+        "max_energy_production",
+    )?;
+    let engine_steam = per_second(engine.fluid_usage_per_tick, engine, "fluid_usage_per_tick")?;
+
+    let engines = count(plan.power / engine_power);
+    let boilers = count(engines as f64 * engine_power / boiler_power);
+    plan.buildings.push((boiler.name.clone(), boilers));
+    plan.buildings.push((engine.name.clone(), engines));
+    // Steam engines consume at most as much steam as boilers produce from water.
+    add_offshore_pumps(info, plan, plan.power / engine_power * engine_steam)?;
+    plan.notes
+        .push(format!("Fuel: {}", format_power(plan.power)));
+    Ok(())
+}
+
+fn plan_solar(info: &Info, plan: &mut PowerPlan) -> anyhow::Result<()> {
+    let panel = entity(info, SOLAR_PANEL)?;
+    let accumulator = entity(info, ACCUMULATOR)?;
+    let surface_property = |name: &str| {
+        info.planet
+            .as_ref()
+            .and_then(|planet| planet.surface_properties.get(name).copied())
+    };
+    // "solar-power" is a percentage of the output on Nauvis.
+    let solar_power = surface_property("solar-power").unwrap_or(100.0) / 100.0;
+    ensure!(solar_power > 0.0, "solar panels don't work on this planet");
+    let cycle = surface_property("day-night-cycle").unwrap_or(DEFAULT_DAY_NIGHT_CYCLE) / 60.0;
+
+    let peak =
+        per_second(panel.max_energy_production, panel, "max_energy_production")? * solar_power;
+    let average_ratio = DAY + (DUSK + DAWN) / 2.0;
+    let panels = count(plan.power / (peak * average_ratio));
+    // During dusk and dawn, output is below the average for `average_ratio` of the time.
+    // At night, all power comes from accumulators.
+    let deficit_ratio = average_ratio * average_ratio * (DUSK + DAWN) / 2.0 + average_ratio * NIGHT;
+    let storage = plan.power / average_ratio * deficit_ratio * cycle;
+    let capacity = accumulator
+        .buffer_capacity
+        .with_context(|| format!("missing buffer_capacity for {}", accumulator.name))?;
+    ensure!(
+        capacity > 0.0,
+        "invalid buffer_capacity for {}",
+        accumulator.name
+    );
+    let accumulators = count(storage / capacity);
+
+    plan.buildings.push((panel.name.clone(), panels));
+    plan.buildings
+        .push((accumulator.name.clone(), accumulators));
+    plan.notes.push(format!(
+        "Peak output: {}, night storage: {} MJ",
+        format_power(panels as f64 * peak),
+        rf(storage / 1e6)
+    ));
+    plan.notes.push(format!(
+        "{} accumulators per solar panel",
+        rf(accumulators as f64 / panels.max(1) as f64)
+    ));
+    Ok(())
+}
+
+/// Total number of neighbours of reactors in a 2 × N layout (or a single reactor).
+fn reactor_neighbours(reactors: u64) -> u64 {
+    match reactors {
+        0 | 1 => 0,
+        2 => 2,
+        // Four corners have 2 neighbours, other reactors have 3.
+        _ => 8 + 3 * reactors.saturating_sub(4),
+    }
+}
+
+fn plan_nuclear(info: &Info, plan: &mut PowerPlan) -> anyhow::Result<()> {
+    let reactor = entity(info, NUCLEAR_REACTOR)?;
+    let exchanger = entity(info, HEAT_EXCHANGER)?;
+    let turbine = entity(info, STEAM_TURBINE)?;
+    let reactor_power = per_second(reactor.max_energy_usage, reactor, "max_energy_usage")?;
+    let neighbour_bonus = reactor.neighbour_bonus.unwrap_or(0.0);
+    let exchanger_power = per_second(exchanger.max_energy_usage, exchanger, "max_energy_usage")?;
+    let turbine_power = per_second(
+        turbine.max_energy_production,
+        turbine,
+        "max_energy_production",
+    )?;
+    let turbine_steam = per_second(
+        turbine.fluid_usage_per_tick,
+        turbine,
+        "fluid_usage_per_tick",
+    )?;
+
+    let heat = |reactors: u64| {
+        reactor_power * (reactors as f64 + neighbour_bonus * reactor_neighbours(reactors) as f64)
+    };
+    // Reactors are placed in 2 × N layouts to use the neighbour bonus.
+    let mut reactors = 1;
+    while heat(reactors) < plan.power - 1e-6 {
+        reactors = if reactors == 1 { 2 } else { reactors + 2 };
+    }
+    plan.buildings.push((reactor.name.clone(), reactors));
+    plan.buildings
+        .push((exchanger.name.clone(), count(plan.power / exchanger_power)));
+    plan.buildings
+        .push((turbine.name.clone(), count(plan.power / turbine_power)));
+    add_offshore_pumps(info, plan, plan.power / turbine_power * turbine_steam)?;
+    let layout = if reactors == 1 {
+        "1".to_string()
+    } else {
+        format!("2 × {}", reactors / 2)
+    };
+    plan.notes.push(format!(
+        "Reactor layout: {layout}, heat: {}",
+        format_power(heat(reactors))
+    ));
+    Ok(())
+}
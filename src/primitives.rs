@@ -7,7 +7,7 @@ use {
     std::{
         fmt::{self, Display, Formatter},
         iter::Sum,
-        ops::{Mul, Neg, Sub, SubAssign},
+        ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
         str::FromStr,
     },
 };
@@ -62,6 +62,36 @@ impl Sum<Speed> for Speed {
     }
 }
 
+impl Add for Speed {
+    type Output = Speed;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        (f64::from(self) + f64::from(rhs)).into()
+    }
+}
+
+impl AddAssign for Speed {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 .0 += rhs.0 .0;
+    }
+}
+
+impl Sub for Speed {
+    type Output = Speed;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        (f64::from(self) - f64::from(rhs)).into()
+    }
+}
+
+impl Div for Speed {
+    type Output = f64;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        f64::from(self) / f64::from(rhs)
+    }
+}
+
 impl SubAssign for Speed {
     fn sub_assign(&mut self, rhs: Self) {
         self.0 .0 -= rhs.0 .0;
@@ -204,6 +234,21 @@ pub struct ModuleName(String);
 )]
 pub struct RecipeCategory(String);
 
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, From, Into, Serialize, Deserialize,
+)]
+pub struct BeaconName(String);
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, From, Into, Serialize, Deserialize,
+)]
+pub struct TechnologyName(String);
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, From, Into, Serialize, Deserialize,
+)]
+pub struct PlanetName(String);
+
 impl Display for ItemName {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -229,6 +274,21 @@ impl Display for RecipeCategory {
         write!(f, "{}", self.0)
     }
 }
+impl Display for BeaconName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl Display for TechnologyName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl Display for PlanetName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 
 impl PartialEq<&str> for ItemName {
     fn eq(&self, other: &&str) -> bool {
@@ -261,6 +321,21 @@ impl RecipeName {
         self.0.as_str()
     }
 }
+impl BeaconName {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+impl TechnologyName {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+impl PlanetName {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
 impl From<&str> for ItemName {
     fn from(value: &str) -> Self {
         value.to_string().into()
@@ -286,11 +361,32 @@ impl From<&str> for RecipeCategory {
         value.to_string().into()
     }
 }
+impl From<&str> for BeaconName {
+    fn from(value: &str) -> Self {
+        value.to_string().into()
+    }
+}
+impl From<&str> for TechnologyName {
+    fn from(value: &str) -> Self {
+        value.to_string().into()
+    }
+}
+impl From<&str> for PlanetName {
+    fn from(value: &str) -> Self {
+        value.to_string().into()
+    }
+}
 
 impl CrafterName {
     pub const SOURCE: Lazy<Self> = Lazy::new(|| "source".into());
     pub const SINK: Lazy<Self> = Lazy::new(|| "sink".into());
 }
+impl BeaconName {
+    /// Beacon used when a snippet doesn't specify one.
+    pub fn default_name() -> Self {
+        "beacon".into()
+    }
+}
 impl RecipeCategory {
     pub const SOURCE: Lazy<Self> = Lazy::new(|| "source".into());
     pub const SINK: Lazy<Self> = Lazy::new(|| "sink".into());
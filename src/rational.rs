@@ -0,0 +1,131 @@
+use {
+    crate::rf,
+    num_bigint::BigInt,
+    num_rational::BigRational,
+    num_traits::{ToPrimitive, Zero},
+};
+
+// Game data is stored as floats, but recipe times, crafting speeds and module effects
+// are simple fractions, so they are recovered with a bounded denominator.
+const MAX_DENOMINATOR: i64 = 1_000_000;
+const TOLERANCE: f64 = 1e-9;
+
+// Larger denominators are not readable, so such values are displayed as floats.
+const MAX_DISPLAY_DENOMINATOR: i64 = 10_000;
+
+/// Simplest fraction that is equal to `value` within the float precision of game data.
+/// Returns `None` if there is no such fraction with a reasonable denominator.
+pub fn to_rational(value: f64) -> Option<BigRational> {
+    if !value.is_finite() {
+        return None;
+    }
+    let tolerance = TOLERANCE * value.abs().max(1.0);
+    // Continued fraction expansion with convergents h/k.
+    let (mut h_prev, mut h) = (BigInt::from(0), BigInt::from(1));
+    let (mut k_prev, mut k) = (BigInt::from(1), BigInt::from(0));
+    let mut rest = value;
+    loop {
+        let whole = rest.floor();
+        let whole_int = BigInt::from(whole as i64);
+        (h_prev, h) = (h.clone(), &whole_int * &h + &h_prev);
+        (k_prev, k) = (k.clone(), &whole_int * &k + &k_prev);
+        if k > BigInt::from(MAX_DENOMINATOR) {
+            return None;
+        }
+        let approximation = BigRational::new(h.clone(), k.clone());
+        if (approximation.to_f64()? - value).abs() <= tolerance {
+            return Some(approximation);
+        }
+        let fraction = rest - whole;
+        if fraction == 0.0 {
+            return None;
+        }
+        rest = 1.0 / fraction;
+    }
+}
+
+/// Solves `a * x = b` exactly with Gauss-Jordan elimination.
+/// Returns `None` if the system has no solution or more than one solution.
+pub fn solve_exact(a: &[Vec<BigRational>], b: &[BigRational]) -> Option<Vec<BigRational>> {
+    let columns = a.first()?.len();
+    let mut rows = a
+        .iter()
+        .zip(b)
+        .map(|(row, value)| {
+            let mut row = row.clone();
+            row.push(value.clone());
+            row
+        })
+        .collect::<Vec<_>>();
+    let mut pivot_row = 0;
+    for column in 0..columns {
+        let pivot = (pivot_row..rows.len()).find(|&row| !rows[row][column].is_zero())?;
+        rows.swap(pivot_row, pivot);
+        let pivot_value = rows[pivot_row][column].clone();
+        for value in &mut rows[pivot_row] {
+            *value /= &pivot_value;
+        }
+        let pivot_values = rows[pivot_row].clone();
+        for (index, row) in rows.iter_mut().enumerate() {
+            if index == pivot_row || row[column].is_zero() {
+                continue;
+            }
+            let factor = row[column].clone();
+            for (value, pivot_value) in row.iter_mut().zip(&pivot_values).skip(column) {
+                *value -= &factor * pivot_value;
+            }
+        }
+        pivot_row += 1;
+    }
+    // Remaining equations have only zero coefficients and must have zero values.
+    if rows[pivot_row..].iter().any(|row| !row[columns].is_zero()) {
+        return None;
+    }
+    Some(
+        rows[..columns]
+            .iter()
+            .map(|row| row[columns].clone())
+            .collect(),
+    )
+}
+
+/// Formats the value as a fraction (e.g. "37/3"), or as a rounded float
+/// if the denominator is too large.
+pub fn format_rational(value: &BigRational) -> String {
+    if value.is_integer() {
+        value.numer().to_string()
+    } else if value.denom() <= &BigInt::from(MAX_DISPLAY_DENOMINATOR) {
+        format!("{}/{}", value.numer(), value.denom())
+    } else {
+        format!("≈{}", rf(value.to_f64().unwrap_or(f64::NAN)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_data_floats_are_recovered() {
+        assert_eq!(
+            to_rational(0.1 + 0.2),
+            Some(BigRational::new(3.into(), 10.into()))
+        );
+        assert_eq!(
+            to_rational(1.0 / 3.0),
+            Some(BigRational::new(1.into(), 3.into()))
+        );
+    }
+
+    #[test]
+    fn beacon_transmission_is_not_a_simple_fraction() {
+        // Transmission of each of 2 beacons is 1/sqrt(2). It is only matched by a long
+        // fraction, which is why exact mode is unavailable with beacons.
+        let value = to_rational(1.0 / 2f64.sqrt()).unwrap();
+        assert!(value.denom() > &BigInt::from(MAX_DISPLAY_DENOMINATOR));
+        assert_ne!(
+            &value * &value * BigInt::from(2),
+            BigRational::from_integer(1.into())
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use {
+    crate::{
+        game_data::GameData,
+        primitives::{CrafterName, ItemName, RecipeName, TechnologyName},
+    },
+    serde::{Deserialize, Serialize},
+    std::collections::BTreeMap,
+};
+
+/// How many times the effects of each technology are applied: 1 for a researched technology,
+/// or the number of researched levels of a technology with levels (e.g. "mining-productivity-3").
+pub type ResearchLevels = BTreeMap<TechnologyName, u32>;
+
+/// Research bonuses that apply to a machine.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ResearchBonus {
+    /// Added to the productivity of the recipe, e.g. 0.1 for +10%.
+    pub productivity: f64,
+    /// Game data already includes the recipe productivity research of the save
+    /// (`Recipe::productivity_bonus`). If the research sets the productivity of the recipe,
+    /// that bonus is replaced instead of added to.
+    pub replaces_recipe_bonus: bool,
+}
+
+/// Total effects of researched technologies.
+#[derive(Debug, Clone, Default)]
+pub struct ResearchBonuses {
+    pub mining_productivity: f64,
+    pub recipe_productivity: BTreeMap<RecipeName, f64>,
+}
+
+impl ResearchBonuses {
+    pub fn new(game_data: &GameData, levels: &ResearchLevels) -> Self {
+        let mut bonuses = ResearchBonuses::default();
+        for (name, level) in levels {
+            let Some(technology) = game_data.technologies.get(name) else {
+                continue;
+            };
+            let level = f64::from(*level);
+            for effect in &technology.effects {
+                match effect.type_.as_str() {
+                    "change-recipe-productivity" => {
+                        if let (Some(recipe), Some(change)) = (&effect.recipe, effect.change) {
+                            *bonuses
+                                .recipe_productivity
+                                .entry(recipe.clone())
+                                .or_default() += change * level;
+                        }
+                    }
+                    "mining-drill-productivity-bonus" => {
+                        bonuses.mining_productivity += effect.modifier.unwrap_or(0.0) * level;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        bonuses
+    }
+
+    pub fn machine_bonus(
+        &self,
+        game_data: &GameData,
+        crafter: &CrafterName,
+        recipe: &RecipeName,
+    ) -> ResearchBonus {
+        let entity = game_data.entities.get(&ItemName::from(crafter.as_str()));
+        let is_mining_drill = entity.is_some_and(|e| e.mining_speed.is_some());
+        let recipe_productivity = self.recipe_productivity.get(recipe).copied();
+        let mut productivity = recipe_productivity.unwrap_or(0.0);
+        if is_mining_drill {
+            productivity += self.mining_productivity;
+        }
+        ResearchBonus {
+            productivity,
+            replaces_recipe_bonus: recipe_productivity.is_some(),
+        }
+    }
+}
+
+/// Levels after the planned research: the higher of the current and the planned level.
+pub fn combined_levels(current: &ResearchLevels, planned: &ResearchLevels) -> ResearchLevels {
+    let mut levels = current.clone();
+    for (name, level) in planned {
+        let entry = levels.entry(name.clone()).or_default();
+        *entry = (*entry).max(*level);
+    }
+    levels
+}
@@ -0,0 +1,92 @@
+use {
+    crate::{
+        info::Info,
+        primitives::{ItemName, Speed},
+    },
+    anyhow::{bail, Context},
+    std::collections::BTreeMap,
+};
+
+// Values of Factorio 2.0, used if the game data was exported by an older script.
+const DEFAULT_ROCKET_LIFT_WEIGHT: f64 = 1_000_000.0;
+const DEFAULT_ROCKET_PARTS_REQUIRED: u32 = 50;
+
+pub const ROCKET_PART: &str = "rocket-part";
+
+/// Rockets needed for one shipped item.
+#[derive(Debug, Clone)]
+pub struct RocketCargo {
+    pub item: ItemName,
+    pub speed: Speed,
+    /// Weight of one item (1000 per kg).
+    pub weight: f64,
+    pub items_per_rocket: u64,
+    /// Average rockets per second needed for this item alone.
+    pub rockets_per_second: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RocketPlan {
+    pub cargo: Vec<RocketCargo>,
+    pub rockets_per_second: f64,
+    pub parts_per_rocket: u32,
+    /// Rate of rocket parts that the rocket silos must craft.
+    pub rocket_parts: Speed,
+}
+
+impl RocketPlan {
+    pub fn rockets_per_minute(&self) -> f64 {
+        self.rockets_per_second * 60.0
+    }
+}
+
+/// Estimates rockets needed to launch the shipped item rates to a space platform or another
+/// planet. Rockets per second are averages: every item type is assumed to fill its own
+/// rockets completely, so partly filled rockets and mixed cargo are not accounted for.
+pub fn plan(info: &Info, shipments: &BTreeMap<ItemName, Speed>) -> anyhow::Result<RocketPlan> {
+    let lift_weight = info
+        .game_data
+        .rocket_lift_weight
+        .unwrap_or(DEFAULT_ROCKET_LIFT_WEIGHT);
+    let parts_per_rocket = info
+        .game_data
+        .entities
+        .values()
+        .find_map(|entity| entity.rocket_parts_required)
+        .unwrap_or(DEFAULT_ROCKET_PARTS_REQUIRED);
+    let mut cargo = Vec::new();
+    for (item, speed) in shipments {
+        if info.is_fluid(item) {
+            bail!("{item} is a fluid and can't be launched in a rocket");
+        }
+        let weight = info
+            .game_data
+            .items
+            .get(item)
+            .and_then(|item| item.weight)
+            .with_context(|| {
+                format!(
+                    "weight of {item} is unknown, export game data again \
+                    with export_game_data.lua"
+                )
+            })?;
+        let items_per_rocket = (lift_weight / weight).floor() as u64;
+        if items_per_rocket == 0 {
+            bail!("{item} is too heavy for a rocket");
+        }
+        cargo.push(RocketCargo {
+            item: item.clone(),
+            speed: *speed,
+            weight,
+            items_per_rocket,
+            rockets_per_second: f64::from(*speed) / items_per_rocket as f64,
+        });
+    }
+    let rockets_per_second: f64 = cargo.iter().map(|c| c.rockets_per_second).sum();
+    Ok(RocketPlan {
+        cargo,
+        rockets_per_second,
+        parts_per_rocket,
+        rocket_parts: (rockets_per_second * f64::from(parts_per_rocket)).into(),
+    })
+}
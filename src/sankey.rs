@@ -0,0 +1,244 @@
+use {
+    crate::{
+        editor::{Editor, EditorMachine},
+        flowchart::{allocate, check_balance, item_flows, Edge},
+        primitives::{ItemName, Speed},
+        ResultExtOrWarn,
+    },
+    itertools::Itertools,
+    std::{
+        collections::BTreeMap,
+        fmt::Write,
+        hash::{DefaultHasher, Hash, Hasher},
+    },
+};
+
+const NODE_WIDTH: f64 = 16.0;
+const COLUMN_WIDTH: f64 = 320.0;
+const NODE_GAP: f64 = 40.0;
+const MARGIN: f64 = 40.0;
+const MAX_COLUMN_HEIGHT: f64 = 800.0;
+const MIN_NODE_HEIGHT: f64 = 2.0;
+
+struct Node {
+    column: usize,
+    value: Speed,
+    y: f64,
+    height: f64,
+}
+
+fn machine_label(editor_machine: &EditorMachine) -> String {
+    let machine = editor_machine.machine();
+    if machine.crafter.is_source() {
+        format!("source: {}", machine.recipe.products[0].name)
+    } else if machine.crafter.is_sink() {
+        format!("sink: {}", machine.recipe.ingredients[0].name)
+    } else {
+        format!(
+            "{} × {} ({})",
+            editor_machine.count_text(),
+            machine.crafter.name,
+            machine.recipe.name
+        )
+    }
+}
+
+fn item_color(item: &ItemName) -> String {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    format!("hsl({}, 65%, 55%)", hasher.finish() % 360)
+}
+
+/// Escapes text for use in SVG element content and attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Renders item flows of the snippet as a self-contained SVG Sankey diagram.
+/// Band widths are proportional to item speed.
+pub fn generate(editor: &Editor, title: &str) -> String {
+    let edges: Vec<Edge> = item_flows(editor)
+        .iter()
+        .flat_map(|flow| {
+            check_balance(flow).or_warn();
+            allocate(flow, editor.info().config.edge_allocation)
+        })
+        .collect();
+    let machines = editor.machines();
+
+    // Place every machine one column to the right of its rightmost supplier.
+    // The number of passes is limited so that cycles don't push nodes indefinitely.
+    let mut columns = vec![0; machines.len()];
+    for _ in 0..machines.len() {
+        let mut changed = false;
+        for edge in &edges {
+            if columns[edge.destination] < columns[edge.source] + 1 {
+                columns[edge.destination] = columns[edge.source] + 1;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let num_columns = columns.iter().max().map_or(1, |max| max + 1);
+    for (index, machine) in machines.iter().enumerate() {
+        if machine.machine().crafter.is_sink() {
+            columns[index] = num_columns - 1;
+        }
+    }
+
+    let mut nodes = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let input: Speed = edges
+                .iter()
+                .filter(|e| e.destination == index)
+                .map(|e| e.speed)
+                .sum();
+            let output: Speed = edges
+                .iter()
+                .filter(|e| e.source == index)
+                .map(|e| e.speed)
+                .sum();
+            Node {
+                column: *column,
+                value: input.max(output),
+                y: 0.0,
+                height: 0.0,
+            }
+        })
+        .collect_vec();
+
+    let max_column_value = (0..num_columns)
+        .map(|column| {
+            nodes
+                .iter()
+                .filter(|n| n.column == column)
+                .map(|n| f64::from(n.value))
+                .sum::<f64>()
+        })
+        .fold(0.0, f64::max);
+    let scale = if max_column_value > 0.0 {
+        MAX_COLUMN_HEIGHT / max_column_value
+    } else {
+        1.0
+    };
+
+    let mut height: f64 = 0.0;
+    for column in 0..num_columns {
+        let mut y = MARGIN + if title.is_empty() { 0.0 } else { 30.0 };
+        for node in nodes.iter_mut().filter(|n| n.column == column) {
+            node.y = y;
+            node.height = (f64::from(node.value) * scale).max(MIN_NODE_HEIGHT);
+            y += node.height + NODE_GAP;
+        }
+        height = height.max(y);
+    }
+    let width = 2.0 * MARGIN + (num_columns - 1) as f64 * COLUMN_WIDTH + NODE_WIDTH;
+    let node_x = |node: &Node| MARGIN + node.column as f64 * COLUMN_WIDTH;
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+        w = width.ceil(),
+        h = height.ceil(),
+    )
+    .unwrap();
+    if !title.is_empty() {
+        writeln!(
+            out,
+            r#"  <text x="{}" y="{}" font-size="20">{}</text>"#,
+            MARGIN,
+            MARGIN,
+            escape_xml(title)
+        )
+        .unwrap();
+    }
+
+    // Bands are grouped by item within each node so that flows of the same item stay adjacent.
+    let mut output_offsets = BTreeMap::<usize, f64>::new();
+    let mut input_offsets = BTreeMap::<usize, f64>::new();
+    let sorted_edges = edges
+        .iter()
+        .sorted_by(|a, b| {
+            (&a.item, nodes[a.destination].column, nodes[a.destination].y)
+                .partial_cmp(&(&b.item, nodes[b.destination].column, nodes[b.destination].y))
+                .unwrap()
+        })
+        .collect_vec();
+    for edge in sorted_edges {
+        let thickness = f64::from(edge.speed) * scale;
+        let source = &nodes[edge.source];
+        let destination = &nodes[edge.destination];
+        let output_offset = output_offsets.entry(edge.source).or_default();
+        let y0 = source.y + *output_offset + thickness / 2.0;
+        *output_offset += thickness;
+        let input_offset = input_offsets.entry(edge.destination).or_default();
+        let y1 = destination.y + *input_offset + thickness / 2.0;
+        *input_offset += thickness;
+
+        let x0 = node_x(source) + NODE_WIDTH;
+        let x1 = node_x(destination);
+        let control_dx = ((x1 - x0).abs() / 2.0).max(COLUMN_WIDTH / 2.0);
+        writeln!(
+            out,
+            r#"  <path d="M{x0},{y0} C{c0},{y0} {c1},{y1} {x1},{y1}" fill="none" stroke="{color}" stroke-opacity="0.5" stroke-width="{thickness}"><title>{speed} {item}</title></path>"#,
+            c0 = x0 + control_dx,
+            c1 = x1 - control_dx,
+            color = item_color(&edge.item),
+            thickness = thickness.max(1.0),
+            speed = escape_xml(&editor.info().format_speed(&edge.item, edge.speed)),
+            item = escape_xml(edge.item.as_str()),
+        )
+        .unwrap();
+        if thickness >= 12.0 {
+            writeln!(
+                out,
+                r#"  <text x="{}" y="{}" dominant-baseline="middle">{} {}</text>"#,
+                x0 + 4.0,
+                y0,
+                escape_xml(&editor.info().format_speed(&edge.item, edge.speed)),
+                escape_xml(edge.item.as_str())
+            )
+            .unwrap();
+        }
+    }
+
+    for (node, machine) in nodes.iter().zip_eq(machines) {
+        let x = node_x(node);
+        writeln!(
+            out,
+            r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="dimgray"><title>{}</title></rect>"#,
+            x,
+            node.y,
+            NODE_WIDTH,
+            node.height,
+            escape_xml(&machine.machine().description(editor.info()))
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"  <text x="{}" y="{}">{}</text>"#,
+            x,
+            node.y - 4.0,
+            escape_xml(&machine_label(machine))
+        )
+        .unwrap();
+    }
+    writeln!(out, "</svg>").unwrap();
+    out
+}
@@ -0,0 +1,98 @@
+use {
+    crate::{editor::BUILDING_COUNT_EPSILON, primitives::ItemName},
+    nalgebra::{DMatrix, DVector},
+};
+
+// Entries of the pseudo-inverse below this are treated as zero.
+const EPSILON: f64 = 1e-9;
+
+/// A constraint that sets the scale of the solution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    ItemProduction(ItemName),
+    MachineCount { machine_index: usize },
+    InputCap(ItemName),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MachineSensitivity {
+    /// Change of a target value per one more machine (or 1/s more of a source),
+    /// if only this target is adjusted to accommodate it.
+    pub target_deltas: Vec<(Target, f64)>,
+    /// Unused part of the last building, from 0 to 1.
+    pub slack: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Sensitivity {
+    /// The constraints allow more than one solution and the solver picked one of them,
+    /// so changes of targets are unknown and `binding` and `target_deltas` are empty.
+    pub ambiguous: bool,
+    /// Targets that have an effect on the solution.
+    pub binding: Vec<Target>,
+    /// Same order as `Editor::machines`.
+    pub machines: Vec<MachineSensitivity>,
+}
+
+impl Sensitivity {
+    pub fn is_binding(&self, target: &Target) -> bool {
+        self.binding.contains(target)
+    }
+}
+
+/// Derives sensitivity from the solver's system `a * x = b`.
+///
+/// `targets` are indexes of rows of `a` that are targets, `x` is the solution.
+/// Column `row` of the pseudo-inverse of `a` is the change of `x` per unit change of `b[row]`,
+/// so a target is binding if that column is not zero, and the inverse of its entries
+/// is the change of the target per unit change of each machine count. This only holds if `x`
+/// is the solution given by the pseudo-inverse, which is not the case if the solver had to
+/// pick a non-negative one of many solutions.
+pub fn compute(
+    a: &DMatrix<f64>,
+    b: &DVector<f64>,
+    targets: &[(usize, Target)],
+    x: &DVector<f64>,
+) -> Sensitivity {
+    let slack = |count: f64| ((count - BUILDING_COUNT_EPSILON).max(0.0).ceil() - count).max(0.0);
+    let Ok(pseudo_inverse) = a.clone().pseudo_inverse(EPSILON) else {
+        return Sensitivity::default();
+    };
+    if (&pseudo_inverse * b - x).amax() > BUILDING_COUNT_EPSILON * x.amax().max(1.0) {
+        return Sensitivity {
+            ambiguous: true,
+            binding: Vec::new(),
+            machines: x
+                .iter()
+                .map(|count| MachineSensitivity {
+                    target_deltas: Vec::new(),
+                    slack: slack(*count),
+                })
+                .collect(),
+        };
+    }
+    let binding = targets
+        .iter()
+        .filter(|(row, _)| pseudo_inverse.column(*row).amax() > EPSILON)
+        .map(|(_, target)| target.clone())
+        .collect();
+    let machines = x
+        .iter()
+        .enumerate()
+        .map(|(machine_index, count)| MachineSensitivity {
+            target_deltas: targets
+                .iter()
+                .filter_map(|(row, target)| {
+                    let derivative = pseudo_inverse[(machine_index, *row)];
+                    (derivative.abs() > EPSILON).then(|| (target.clone(), 1.0 / derivative))
+                })
+                .collect(),
+            slack: slack(*count),
+        })
+        .collect();
+    Sensitivity {
+        ambiguous: false,
+        binding,
+        machines,
+    }
+}
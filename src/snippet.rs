@@ -1,5 +1,13 @@
 use {
-    crate::primitives::{CrafterName, ItemName, MachineCount, ModuleName, RecipeName, Speed},
+    crate::{
+        power::PowerSource,
+        primitives::{
+            BeaconName, CrafterName, ItemName, MachineCount, ModuleName, PlanetName, RecipeName,
+            Speed,
+        },
+        research::ResearchLevels,
+    },
+    ordered_float::OrderedFloat,
     serde::{Deserialize, Serialize},
     std::collections::BTreeMap,
 };
@@ -40,10 +48,135 @@ pub struct Snippet {
     pub machines: Vec<MachineSnippet>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub item_speed_constraints: BTreeMap<ItemName, Speed>,
+    /// Maximum consumption of inputs. They reduce the solution if other constraints need more.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub input_caps: BTreeMap<ItemName, Speed>,
+    /// Items without a policy get a source or a sink automatically if they are only consumed
+    /// or only produced.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub item_policies: BTreeMap<ItemName, ItemPolicy>,
+    /// Round crafter counts up to whole buildings.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub integer_machine_counts: bool,
+    /// Solve with exact fractions and display crafter counts as fractions.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub exact_machine_counts: bool,
+    /// Research that is planned but not done yet; the current research is in the config.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub planned_research: ResearchLevels,
+    /// Show the snippet after the planned research.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub use_planned_research: bool,
+    /// Recipes and crafters must meet surface conditions of the planet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planet: Option<PlanetName>,
+    /// Average time items spend between machines, in seconds. Used for spoilage estimates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transit_time: Option<OrderedFloat<f64>>,
+    /// Warn if spoilable items take longer than this to reach consumers, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_latency: Option<OrderedFloat<f64>>,
+    /// Item rates launched by rockets to a space platform or another planet.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rocket_shipments: BTreeMap<ItemName, Speed>,
+    /// Power plants that are part of the snippet, with the power they must generate in watts.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub power_plants: BTreeMap<PowerSource, OrderedFloat<f64>>,
+    /// Round trip time of trains that serve sources and sinks, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub train_round_trip_time: Option<OrderedFloat<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wagons_per_train: Option<u32>,
+    /// Wagons of train estimates. The first wagon of each type in game data is used if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo_wagon: Option<ItemName>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fluid_wagon: Option<ItemName>,
+}
+
+/// How the solver treats the difference between production and consumption of an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ItemPolicy {
+    /// Production must be equal to consumption, no source or sink is added.
+    Balance,
+    /// Extra production goes to a sink.
+    AllowSurplus,
+    /// Missing amount comes from a source.
+    AllowDeficit,
+    /// Extra production is destroyed by a recipe without products (e.g. a flare stack)
+    /// if the game has one, otherwise it goes to a sink.
+    Void,
+}
+
+impl ItemPolicy {
+    pub const ALL: [ItemPolicy; 4] = [
+        ItemPolicy::Balance,
+        ItemPolicy::AllowSurplus,
+        ItemPolicy::AllowDeficit,
+        ItemPolicy::Void,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemPolicy::Balance => "Must balance",
+            ItemPolicy::AllowSurplus => "Allow surplus",
+            ItemPolicy::AllowDeficit => "Allow deficit",
+            ItemPolicy::Void => "Void",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
-#[serde(transparent)]
+#[serde(from = "BeaconSnippetRepr")]
 pub struct BeaconSnippet {
+    /// Default beacon type is used if not specified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beacon: Option<BeaconName>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub quality: u32,
     pub modules: Vec<ModuleName>,
 }
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+// Beacons used to be saved as a plain list of modules.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BeaconSnippetRepr {
+    Modules(Vec<ModuleName>),
+    Beacon {
+        #[serde(default)]
+        beacon: Option<BeaconName>,
+        #[serde(default)]
+        quality: u32,
+        modules: Vec<ModuleName>,
+    },
+}
+
+impl From<BeaconSnippetRepr> for BeaconSnippet {
+    fn from(value: BeaconSnippetRepr) -> Self {
+        match value {
+            BeaconSnippetRepr::Modules(modules) => BeaconSnippet {
+                beacon: None,
+                quality: 0,
+                modules,
+            },
+            BeaconSnippetRepr::Beacon {
+                beacon,
+                quality,
+                modules,
+            } => BeaconSnippet {
+                beacon,
+                quality,
+                modules,
+            },
+        }
+    }
+}
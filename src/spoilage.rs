@@ -0,0 +1,192 @@
+use {
+    crate::{
+        editor::Editor,
+        flowchart::{allocate, item_flows},
+        primitives::{ItemName, Speed},
+        rf,
+    },
+    std::collections::BTreeMap,
+};
+
+/// If no latency limit is set, items that lose more than this fraction of their freshness
+/// before reaching consumers are reported.
+const FRESHNESS_LOSS_WARNING: f64 = 0.5;
+
+/// Spoilage of an item along the chain.
+#[derive(Debug, Clone)]
+pub struct ItemSpoilage {
+    pub item: ItemName,
+    /// Spoil time of the item, in seconds.
+    pub spoil_time: f64,
+    /// Maximum time from the first spoilable ingredient to a consumer of the item, in seconds.
+    pub latency: f64,
+    /// Lowest freshness of the item when it reaches a consumer, from 0 to 1.
+    pub freshness: f64,
+    /// Estimated amount of the item that spoils before it reaches consumers.
+    pub spoiled: Speed,
+    pub spoil_result: Option<ItemName>,
+    pub warning: Option<String>,
+}
+
+/// State of spoilable products when they leave a machine.
+#[derive(Debug, Clone, Copy)]
+struct Departure {
+    latency: f64,
+    freshness: f64,
+}
+
+const FRESH: Departure = Departure {
+    latency: 0.0,
+    freshness: 1.0,
+};
+
+/// Estimates spoilage of all spoilable items of the solved snippet.
+///
+/// Spoilage is linear: an item spoils when it has been in transit for its spoil time.
+/// Items wait in belts, inserters and chests for `transit_time` on average, and waiting
+/// times are assumed to be exponentially distributed, so a part of the items spoils
+/// even if the average item arrives fresh. The product of a recipe is as fresh as its
+/// stalest spoilable ingredient.
+pub fn analyze(editor: &Editor) -> Vec<ItemSpoilage> {
+    let info = editor.info();
+    let transit_time = editor.transit_time();
+    let spoil_time = |item: &ItemName| {
+        info.game_data
+            .items
+            .get(item)
+            .and_then(|item| item.spoil_time())
+    };
+    let edges = item_flows(editor)
+        .iter()
+        .filter(|flow| spoil_time(&flow.item).is_some())
+        .flat_map(|flow| allocate(flow, info.config.edge_allocation))
+        .collect::<Vec<_>>();
+
+    let machines = editor.machines();
+    let arrival = |departures: &[Departure], source: usize, item: &ItemName| {
+        let departure = departures[source];
+        Departure {
+            latency: departure.latency + transit_time,
+            freshness: departure.freshness
+                - transit_time / spoil_time(item).unwrap_or(f64::INFINITY),
+        }
+    };
+    // Longest paths through the chain. Each pass extends paths by one machine,
+    // so cycles stop growing after all machines were visited.
+    let mut departures = vec![FRESH; machines.len()];
+    for _ in 0..machines.len() {
+        let mut changed = false;
+        for (index, machine) in machines.iter().enumerate() {
+            let machine = machine.machine();
+            if machine.crafter.is_source_or_sink() {
+                continue;
+            }
+            let craft_time = machine.recipe.energy
+                / (machine.crafter.crafting_speed * machine.speed_multiplier());
+            let mut departure = Departure {
+                latency: craft_time,
+                freshness: 1.0,
+            };
+            for edge in edges.iter().filter(|edge| edge.destination == index) {
+                let input = arrival(&departures, edge.source, &edge.item);
+                departure.latency = departure.latency.max(input.latency + craft_time);
+                departure.freshness = departure.freshness.min(input.freshness);
+            }
+            let percent_spoiled = machine
+                .recipe
+                .products
+                .iter()
+                .map(|product| product.percent_spoiled)
+                .fold(0.0, f64::max);
+            departure.freshness = departure.freshness.min(1.0 - percent_spoiled);
+            let old = departures[index];
+            if (departure.latency - old.latency).abs() > f64::EPSILON
+                || (departure.freshness - old.freshness).abs() > f64::EPSILON
+            {
+                departures[index] = departure;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut spoilage = BTreeMap::<ItemName, ItemSpoilage>::new();
+    for edge in &edges {
+        let Some(item_spoil_time) = spoil_time(&edge.item) else {
+            continue;
+        };
+        let input = arrival(&departures, edge.source, &edge.item);
+        let remaining_time = departures[edge.source].freshness.max(0.0) * item_spoil_time;
+        let spoiled_fraction = if input.freshness <= 0.0 {
+            1.0
+        } else if transit_time > 0.0 {
+            (-remaining_time / transit_time).exp()
+        } else {
+            0.0
+        };
+        let entry = spoilage
+            .entry(edge.item.clone())
+            .or_insert_with(|| ItemSpoilage {
+                item: edge.item.clone(),
+                spoil_time: item_spoil_time,
+                latency: 0.0,
+                freshness: 1.0,
+                spoiled: Speed::ZERO,
+                spoil_result: info
+                    .game_data
+                    .items
+                    .get(&edge.item)
+                    .and_then(|item| item.spoil_result.clone()),
+                warning: None,
+            });
+        entry.latency = entry.latency.max(input.latency);
+        entry.freshness = entry.freshness.min(input.freshness);
+        entry.spoiled += edge.speed * spoiled_fraction;
+    }
+
+    let max_latency = editor.max_latency();
+    let mut spoilage = spoilage.into_values().collect::<Vec<_>>();
+    for item in &mut spoilage {
+        item.warning = if item.freshness <= 0.0 {
+            Some(format!(
+                "{} spoils before it reaches consumers ({}s latency, {}s spoil time)",
+                item.item,
+                rf(item.latency),
+                rf(item.spoil_time)
+            ))
+        } else if let Some(max_latency) = max_latency {
+            (item.latency > max_latency).then(|| {
+                format!(
+                    "{} latency is {}s, more than {}s",
+                    item.item,
+                    rf(item.latency),
+                    rf(max_latency)
+                )
+            })
+        } else {
+            (1.0 - item.freshness > FRESHNESS_LOSS_WARNING).then(|| {
+                format!(
+                    "{} loses {}% of its freshness before it reaches consumers",
+                    item.item,
+                    rf((1.0 - item.freshness) * 100.0)
+                )
+            })
+        };
+    }
+    spoilage
+}
+
+/// Production rates of spoil results (e.g. "spoilage") of items that spoil in transit.
+pub fn byproducts(spoilage: &[ItemSpoilage]) -> BTreeMap<ItemName, Speed> {
+    let mut byproducts = BTreeMap::<ItemName, Speed>::new();
+    for item in spoilage {
+        if let Some(result) = &item.spoil_result {
+            if item.spoiled > Speed::ZERO {
+                *byproducts.entry(result.clone()).or_insert(Speed::ZERO) += item.spoiled;
+            }
+        }
+    }
+    byproducts
+}
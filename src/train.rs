@@ -0,0 +1,109 @@
+use {
+    crate::{
+        info::Info,
+        primitives::{ItemName, Speed},
+    },
+    anyhow::{ensure, Context},
+    itertools::Itertools,
+};
+
+// Values of the base game, used if the game data was exported by an older script.
+const DEFAULT_CARGO_WAGON_SLOTS: u64 = 40;
+const DEFAULT_FLUID_WAGON_CAPACITY: f64 = 50_000.0;
+
+/// Time for a train to load, travel to the unloading station, unload and return, in seconds.
+pub const DEFAULT_ROUND_TRIP_TIME: f64 = 120.0;
+pub const DEFAULT_WAGONS_PER_TRAIN: u32 = 4;
+
+/// Trains needed to transport an item rate.
+#[derive(Debug, Clone)]
+pub struct TrainLoad {
+    /// Items (or fluid units) in a full wagon.
+    pub wagon_capacity: f64,
+    pub wagons_per_minute: f64,
+    pub trains_per_minute: f64,
+    /// Trains that must be on the route at the same time.
+    pub trains: u64,
+}
+
+/// Entity type of wagons that carry the item: "fluid-wagon" or "cargo-wagon".
+pub fn wagon_type(info: &Info, item: &ItemName) -> &'static str {
+    if info.is_fluid(item) {
+        "fluid-wagon"
+    } else {
+        "cargo-wagon"
+    }
+}
+
+/// Wagons of the type in game data, the one named after the type first.
+pub fn wagons<'a>(info: &'a Info, type_: &str) -> Vec<&'a ItemName> {
+    info.game_data
+        .entities
+        .values()
+        .filter(|entity| entity.type_ == type_)
+        .sorted_by_key(|entity| entity.name.as_str() != type_)
+        .map(|entity| &entity.name)
+        .collect()
+}
+
+fn wagon_capacity(info: &Info, item: &ItemName, wagon: Option<&ItemName>) -> anyhow::Result<f64> {
+    let type_ = wagon_type(info, item);
+    let wagon = match wagon {
+        Some(name) => {
+            let entity = info
+                .game_data
+                .entities
+                .get(name)
+                .filter(|entity| entity.type_ == type_)
+                .with_context(|| format!("{name} is not a {type_} in game data"))?;
+            Some(entity)
+        }
+        None => wagons(info, type_)
+            .first()
+            .and_then(|name| info.game_data.entities.get(*name)),
+    };
+    if info.is_fluid(item) {
+        return Ok(wagon
+            .and_then(|wagon| wagon.fluid_capacity)
+            .unwrap_or(DEFAULT_FLUID_WAGON_CAPACITY));
+    }
+    let stack_size = info
+        .game_data
+        .items
+        .get(item)
+        .and_then(|item| item.stack_size)
+        .with_context(|| {
+            format!(
+                "stack size of {item} is unknown, export game data again \
+                with export_game_data.lua"
+            )
+        })?;
+    let slots = wagon
+        .and_then(|wagon| wagon.inventory_size)
+        .unwrap_or(DEFAULT_CARGO_WAGON_SLOTS);
+    Ok((stack_size * slots) as f64)
+}
+
+/// Computes wagons and trains needed to transport `speed` of `item` with full wagons.
+/// The first wagon of `wagon_type` is used if `wagon` is not set.
+pub fn train_load(
+    info: &Info,
+    item: &ItemName,
+    speed: Speed,
+    round_trip_time: f64,
+    wagons_per_train: u32,
+    wagon: Option<&ItemName>,
+) -> anyhow::Result<TrainLoad> {
+    ensure!(wagons_per_train > 0, "a train must have at least one wagon");
+    let wagon_capacity = wagon_capacity(info, item, wagon)?;
+    ensure!(wagon_capacity > 0.0, "{item} doesn't fit into a wagon");
+    let wagons_per_second = f64::from(speed).abs() / wagon_capacity;
+    let trains_per_second = wagons_per_second / f64::from(wagons_per_train);
+    Ok(TrainLoad {
+        wagon_capacity,
+        wagons_per_minute: wagons_per_second * 60.0,
+        trains_per_minute: trains_per_second * 60.0,
+        // Avoid an extra train because of float errors.
+        trains: (trains_per_second * round_trip_time - 1e-9).ceil().max(0.0) as u64,
+    })
+}
@@ -1,21 +1,28 @@
 use {
-    super::drop_down::DropDownOption,
+    super::{costs_ui::CostColumn, drop_down::DropDownOption},
     crate::{
+        bom::BillOfMaterials,
+        config::RecipeFilter,
+        cost::ItemCost,
         editor::Editor,
+        export::exporter,
         flowchart,
         game_data::Recipe,
         info::Info,
         machine::Module,
-        primitives::{CrafterName, ItemName, RecipeName, Speed},
+        optimizer::{Objective, Optimization},
+        power::PowerSource,
+        primitives::{BeaconName, CrafterName, ItemName, RecipeName},
+        sankey,
+        snippet::{ItemPolicy, MachineSnippet},
         ResultExtOrWarn,
     },
     anyhow::{format_err, Context},
     arboard::Clipboard,
-    eframe::egui::{Align, Id, Layout, Widget},
-    itertools::Itertools,
+    eframe::egui::{Align, Id, Layout, RichText, Widget},
     std::{
         borrow::Cow,
-        collections::{BTreeSet, VecDeque},
+        collections::{BTreeMap, BTreeSet, VecDeque},
         env,
         ffi::OsStr,
         path::Path,
@@ -51,7 +58,6 @@ pub struct MyApp {
 
     // Static data
     pub all_recipe_menu_items: Vec<RecipeMenuItem>,
-    pub belt_speeds: Vec<(Speed, ItemName)>,
     pub default_speed_module: Module,
     pub default_productivity_module: Module,
 
@@ -67,6 +73,56 @@ pub struct MyApp {
     pub saved: bool,
     pub confirm_delete: Option<String>,
 
+    // Export
+    pub export_format: &'static str,
+
+    // Item balance
+    pub show_item_balance: bool,
+
+    // Bill of materials
+    pub show_bill_of_materials: bool,
+    pub bill_of_materials_logistics: bool,
+    pub bill_of_materials: BillOfMaterials,
+    pub bill_of_materials_generation: Option<u64>,
+
+    // Item costs
+    pub show_item_costs: bool,
+    pub item_costs: BTreeMap<ItemName, ItemCost>,
+    pub item_costs_generation: Option<u64>,
+    pub item_costs_sort: (CostColumn, bool),
+
+    // Module optimizer
+    pub show_optimizer: bool,
+    pub optimizer_objective: Objective,
+    pub optimizer_max_beacons: String,
+    // (result, generation it was computed for)
+    pub optimization: Option<(Optimization, u64)>,
+
+    // Research
+    pub show_research: bool,
+    pub research_technology: String,
+    pub research_level: String,
+
+    // Spoilage
+    pub show_spoilage: bool,
+    pub transit_time: String,
+    pub max_latency: String,
+
+    // Rockets
+    pub show_rockets: bool,
+    pub rocket_item: String,
+    pub rocket_speed: String,
+
+    // Power generation
+    pub show_power: bool,
+    pub power_source: PowerSource,
+    pub power_demand: String,
+
+    // Trains
+    pub show_trains: bool,
+    pub train_round_trip_time: String,
+    pub wagons_per_train: String,
+
     // Add recipe
     pub recipe_search_text: String,
 
@@ -80,11 +136,25 @@ pub struct MyApp {
     pub old_item_speed_contraint_item: String,
     pub item_speed_contraint_speed: String,
 
+    // Input caps
+    pub input_cap_item: String,
+    pub input_cap: String,
+
+    // Scale
+    pub scale_factor: String,
+    pub fit_machine_index: Option<usize>,
+    pub fit_limit: String,
+
+    // Item policies
+    pub item_policy_item: String,
+    pub item_policy: ItemPolicy,
+
     // Edit machine
     pub edit_machine_index: Option<usize>,
     pub machine_count_constraint: String,
     pub focus_machine_constraint_input: bool,
     pub num_beacons: String,
+    pub beacon_type: BeaconName,
 }
 
 const UNTITLED: &str = "Untitled";
@@ -114,23 +184,6 @@ impl MyApp {
                     .to_string(),
             );
         }
-        let mut belt_speeds = editor
-            .info()
-            .game_data
-            .entities
-            .values()
-            .filter(|e| e.type_ == "transport-belt")
-            .map(|e| {
-                // belt_speed is tiles per tick;
-                // throughput per second = belt_speed * 60 (ticks/s) * 8 (density)
-                (
-                    Speed::from(e.belt_speed.expect("missing belt_speed") * 60. * 8.),
-                    e.name.clone(),
-                )
-            })
-            .collect_vec();
-        belt_speeds.sort_by_key(|(speed, _)| *speed);
-
         let (speed_module_name, prod_module_name) = match editor.info().config.module_tier {
             1 => ("speed-module", "productivity-module"),
             2 => ("speed-module-2", "productivity-module-2"),
@@ -159,32 +212,75 @@ impl MyApp {
             item_speed_contraint_item: String::new(),
             old_item_speed_contraint_item: String::new(),
             item_speed_contraint_speed: String::new(),
+            input_cap_item: String::new(),
+            input_cap: String::new(),
+            scale_factor: "2".into(),
+            fit_machine_index: None,
+            fit_limit: String::new(),
+            item_policy_item: String::new(),
+            item_policy: ItemPolicy::AllowSurplus,
             machine_count_constraint: String::new(),
             all_recipe_menu_items: Vec::new(),
             snippet_name: String::new(),
             snippet_names,
             saved: false,
             confirm_delete: None,
+            export_format: "text",
+            show_item_balance: false,
+            show_bill_of_materials: false,
+            bill_of_materials_logistics: false,
+            bill_of_materials: BillOfMaterials::default(),
+            bill_of_materials_generation: None,
+            show_item_costs: false,
+            item_costs: BTreeMap::new(),
+            item_costs_generation: None,
+            item_costs_sort: (CostColumn::Item, false),
+            show_optimizer: false,
+            optimizer_objective: Objective::BuildingCount,
+            optimizer_max_beacons: "8".into(),
+            optimization: None,
+            show_research: false,
+            research_technology: String::new(),
+            research_level: "1".into(),
+            show_spoilage: false,
+            transit_time: String::new(),
+            max_latency: String::new(),
+            show_rockets: false,
+            rocket_item: String::new(),
+            rocket_speed: String::new(),
+            show_power: false,
+            power_source: PowerSource::Steam,
+            power_demand: String::new(),
+            show_trains: false,
+            train_round_trip_time: String::new(),
+            wagons_per_train: String::new(),
             generation: 0,
             edit_machine_index: None,
             replace_with_craft_options: Vec::new(),
             replace_with_craft_index: None,
-            belt_speeds,
             focus_machine_constraint_input: false,
             default_speed_module,
             default_productivity_module,
             num_beacons: String::new(),
+            beacon_type: BeaconName::default_name(),
         };
-        app.all_recipe_menu_items = app
+        app.update_recipe_menu_items();
+        app.reset_spoilage_inputs();
+        app.reset_train_inputs();
+
+        Ok(app)
+    }
+
+    /// Rebuilds the recipe menu after the research or the recipe filter changed.
+    pub fn update_recipe_menu_items(&mut self) {
+        self.all_recipe_menu_items = self
             .editor
             .info()
             .game_data
             .recipes
             .values()
-            .flat_map(|recipe| recipe_menu_items(app.editor.info(), recipe))
+            .flat_map(|recipe| recipe_menu_items(self.editor.info(), recipe))
             .collect();
-
-        Ok(app)
     }
 
     pub fn add_crafter(
@@ -225,9 +321,36 @@ impl MyApp {
         Ok(())
     }
 
+    pub fn save_sankey(&self) -> anyhow::Result<()> {
+        let svg = sankey::generate(&self.editor, name_or_untitled(&self.snippet_name));
+        fs_err::create_dir_all("sankey")?;
+        let file_path = std::env::current_dir()?.join(format!(
+            "sankey/{}.svg",
+            name_or_untitled(&self.snippet_name)
+        ));
+        fs_err::write(&file_path, svg)?;
+        Ok(())
+    }
+
+    pub fn open_sankey(&self) -> anyhow::Result<()> {
+        self.save_sankey()?;
+        let file_path = std::env::current_dir()?.join(format!(
+            "sankey/{}.svg",
+            name_or_untitled(&self.snippet_name)
+        ));
+        let url = Url::from_file_path(file_path)
+            .map_err(|()| format_err!("Url::from_file_path failed"))?;
+        open::that(url.as_str())?;
+        Ok(())
+    }
+
     pub fn load_snippet(&mut self, name: &str) -> anyhow::Result<()> {
         self.generation += 1;
         self.editor.load_snippet(format!("snippets/{name}.json"))?;
+        // Planned research of the snippet can unlock recipes.
+        self.update_recipe_menu_items();
+        self.reset_spoilage_inputs();
+        self.reset_train_inputs();
         self.snippet_name = name.into();
         self.saved = true;
         Ok(())
@@ -247,6 +370,22 @@ impl MyApp {
         Ok(())
     }
 
+    pub fn start_editing_machine(&mut self, index: usize) {
+        let Some(editor_machine) = self.editor.machines().get(index) else {
+            return;
+        };
+        let MachineSnippet::Crafter(crafter) = editor_machine.snippet() else {
+            return;
+        };
+        self.machine_count_constraint = crafter
+            .count_constraint
+            .map(|c| c.to_string())
+            .unwrap_or_default();
+        self.num_beacons = editor_machine.machine().beacons.len().to_string();
+        self.edit_machine_index = Some(index);
+        self.focus_machine_constraint_input = true;
+    }
+
     pub fn after_machines_changed(&mut self) {
         self.generation += 1;
         self.save_snippet().or_warn();
@@ -263,25 +402,46 @@ impl MyApp {
         self.snippet_name = String::new();
         self.saved = false;
         self.editor.clear();
+        self.update_recipe_menu_items();
+        self.reset_spoilage_inputs();
+        self.reset_train_inputs();
     }
 
     pub fn delete_snippet(&mut self, name: &str) -> anyhow::Result<()> {
         let snippet_path = format!("snippets/{}.json", name_or_untitled(name));
         let mermaid_path = format!("mermaid/{}.html", name_or_untitled(name));
+        let sankey_path = format!("sankey/{}.svg", name_or_untitled(name));
         fs_err::remove_file(snippet_path)?;
         if Path::new(&mermaid_path).try_exists()? {
             fs_err::remove_file(mermaid_path)?;
         }
+        if Path::new(&sankey_path).try_exists()? {
+            fs_err::remove_file(sankey_path)?;
+        }
         self.snippet_names.remove(name);
         self.new_snippet();
         Ok(())
     }
 
-    pub fn copy_description(&self) -> anyhow::Result<()> {
-        let text = self.editor.description();
+    pub fn copy_export(&self) -> anyhow::Result<()> {
+        let text = exporter(self.export_format)?.export(&self.editor)?;
         Clipboard::new()?.set_text(&text)?;
         Ok(())
     }
+
+    pub fn save_export(&self) -> anyhow::Result<()> {
+        let exporter = exporter(self.export_format)?;
+        fs_err::create_dir_all("exports")?;
+        fs_err::write(
+            format!(
+                "exports/{}.{}",
+                name_or_untitled(&self.snippet_name),
+                exporter.extension()
+            ),
+            exporter.export(&self.editor)?,
+        )?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -290,6 +450,8 @@ pub struct RecipeMenuItem {
     crafter: Option<CrafterName>,
     text: String,
     id: Id,
+    // Not researched yet, shown greyed out.
+    locked: bool,
 }
 
 impl RecipeMenuItem {
@@ -303,9 +465,14 @@ impl RecipeMenuItem {
             },
             recipe,
             crafter,
+            locked: false,
         }
     }
 
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+
     pub fn text(&self) -> &str {
         &self.text
     }
@@ -330,7 +497,12 @@ impl Widget for &RecipeMenuItem {
 
             ui.with_layout(layout, |ui| {
                 ui.spacing_mut().button_padding.y = 3.;
-                r = Some(ui.selectable_label(false, &self.text));
+                r = Some(if self.locked {
+                    ui.selectable_label(false, RichText::new(&self.text).weak())
+                        .on_hover_text("Not researched")
+                } else {
+                    ui.selectable_label(false, &self.text)
+                });
             });
         });
         r.unwrap_or(out_r.response)
@@ -351,6 +523,9 @@ impl DropDownOption for &RecipeMenuItem {
     }
 }
 
+/// Menu items for the recipe: one item if the crafter can be selected automatically,
+/// otherwise one item per crafter. Recipes and crafters that can't be used on the planet
+/// are skipped, and ones that don't pass `Config::recipe_filter` are skipped or marked as locked.
 pub fn recipe_menu_items(info: &Info, recipe: &Recipe) -> Vec<RecipeMenuItem> {
     // let recipe_text = if recipe.products.len() != 1 || recipe.products[0].name != recipe.name {
     //     format!(
@@ -367,13 +542,35 @@ pub fn recipe_menu_items(info: &Info, recipe: &Recipe) -> Vec<RecipeMenuItem> {
         .category_to_crafter
         .get(&recipe.category)
         .expect("missing item in category_to_crafter");
+    let show_locked = info.config.recipe_filter == RecipeFilter::ShowLocked;
+    if !info.is_recipe_possible(recipe) || (!info.is_recipe_offered(recipe) && !show_locked) {
+        return Vec::new();
+    }
+    let recipe_locked = !info.is_recipe_offered(recipe);
 
-    if info.auto_select_crafter(crafters).is_some() {
-        vec![RecipeMenuItem::new(recipe.name.clone(), None)]
+    let items = if let Some(crafter) = info.auto_select_crafter(crafters) {
+        let mut item = RecipeMenuItem::new(recipe.name.clone(), None);
+        item.locked = !info.is_crafter_offered(&crafter);
+        vec![item]
     } else {
         crafters
             .iter()
-            .map(move |crafter| RecipeMenuItem::new(recipe.name.clone(), Some(crafter.clone())))
+            .filter(|crafter| {
+                info.is_crafter_possible(crafter)
+                    && (show_locked || info.is_crafter_offered(crafter))
+            })
+            .map(|crafter| {
+                let mut item = RecipeMenuItem::new(recipe.name.clone(), Some(crafter.clone()));
+                item.locked = !info.is_crafter_offered(crafter);
+                item
+            })
             .collect()
-    }
+    };
+    items
+        .into_iter()
+        .map(|mut item| {
+            item.locked |= recipe_locked;
+            item
+        })
+        .collect()
 }
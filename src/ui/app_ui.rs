@@ -1,19 +1,26 @@
 use {
     super::{
         app::{recipe_menu_items, MyApp, RecipeMenuItem},
+        costs_ui::item_cost_tooltip,
         drop_down::DropDownBox,
+        sensitivity_ui::show_machine_sensitivity,
+        train_ui::show_machine_trains,
         ui_ext::UiExt,
     },
     crate::{
-        machine::Beacon,
+        config::RateUnit,
+        export::exporters,
+        machine::{format_emissions, Beacon},
         module_counts,
-        primitives::{CrafterName, RecipeName, Speed},
+        power::format_power,
+        primitives::{CrafterName, ItemName, RecipeName, Speed},
         rf,
+        sensitivity::Target,
         snippet::{CrafterSnippet, MachineSnippet},
         ResultExtOrWarn,
     },
-    eframe::egui::{self, Color32, ComboBox, Frame, Key},
-    egui::{Response, ScrollArea, TextEdit, Ui, Widget},
+    eframe::egui::{self, Checkbox, Color32, ComboBox, Frame, Key},
+    egui::{Response, RichText, ScrollArea, TextEdit, Ui, Widget},
     itertools::Itertools,
     std::time::{Duration, Instant},
 };
@@ -21,6 +28,7 @@ use {
 impl MyApp {
     pub fn show(&mut self, ui: &mut Ui) -> Response {
         let mut focus_speed_constraint_input = false;
+        self.update_item_costs();
 
         while let Ok(msg) = self.msg_receiver.try_recv() {
             self.alerts.push_back((msg, Instant::now()));
@@ -143,13 +151,67 @@ impl MyApp {
                     });
                 });
 
-                ui.heading("Machines");
+                ui.horizontal(|ui| {
+                    ui.heading("Machines");
+                    let mut integer_machine_counts = self.editor.integer_machine_counts();
+                    if ui
+                        .checkbox(&mut integer_machine_counts, "Whole buildings")
+                        .changed()
+                    {
+                        self.editor
+                            .set_integer_machine_counts(integer_machine_counts);
+                        self.after_machines_changed();
+                    }
+                    if !self.editor.info().game_data.planets.is_empty() {
+                        ui.label("Planet:");
+                        let mut planet = self.editor.planet().cloned();
+                        ComboBox::new("planet", "")
+                            .selected_text(planet.as_ref().map_or("any", |p| p.as_str()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut planet, None, "any");
+                                for name in self.editor.info().game_data.planets.keys() {
+                                    ui.selectable_value(
+                                        &mut planet,
+                                        Some(name.clone()),
+                                        name.as_str(),
+                                    );
+                                }
+                            });
+                        if planet.as_ref() != self.editor.planet() {
+                            self.saved = false;
+                            self.alerts.clear();
+                            self.editor.set_planet(planet).or_warn();
+                            self.update_recipe_menu_items();
+                            self.after_machines_changed();
+                        }
+                    }
+                    let mut exact_machine_counts = self.editor.exact_machine_counts();
+                    // Exact mode can still be turned off after beacons are added.
+                    let exact_enabled = !self.editor.has_beacons() || exact_machine_counts;
+                    if ui
+                        .add_enabled(
+                            exact_enabled,
+                            Checkbox::new(&mut exact_machine_counts, "Exact machine counts"),
+                        )
+                        .on_hover_text("Machine counts are solved as fractions, rates are rounded")
+                        .on_disabled_hover_text("Beacon effects are not simple fractions")
+                        .changed()
+                    {
+                        self.editor.set_exact_machine_counts(exact_machine_counts);
+                        self.after_machines_changed();
+                    }
+                    ui.checkbox(&mut self.show_trains, "Trains");
+                });
+                if self.show_trains {
+                    self.show_train_settings(ui);
+                }
                 //let show_names = ui.input(|i| i.modifiers.alt);
                 egui::Frame::group(ui.style()).show(ui, |ui| {
                     if self.editor.machines().is_empty() {
                         ui.label("No machines.");
                     }
                     let mut index_to_remove = None;
+                    let mut machine_to_edit = None;
                     let mut recipe_to_add: Option<(RecipeName, Option<CrafterName>)> = None;
                     for (i, editor_machine) in self.editor.machines().iter().enumerate() {
                         let machine = editor_machine.machine();
@@ -162,10 +224,13 @@ impl MyApp {
                                     for stack in &item_speeds {
                                         if stack.speed < Speed::ZERO {
                                             ui.rich_label(format!(
-                                                "{}{} @[{}:]",
+                                                "{}{} @[{}:{}]",
                                                 if is_first { "" } else { "+ " },
-                                                -stack.speed,
+                                                self.editor
+                                                    .info()
+                                                    .format_speed(&stack.item, -stack.speed),
                                                 stack.item,
+                                                item_cost_tooltip(&self.item_costs, &stack.item),
                                             ));
                                             is_first = false;
                                         }
@@ -183,7 +248,16 @@ impl MyApp {
                                 } else {
                                     String::new()
                                 };
-                                format!("{}{} × ", lock, rf(machine.crafter_count))
+                                if self.editor.integer_machine_counts() {
+                                    format!(
+                                        "{}{} × ({}%) ",
+                                        lock,
+                                        editor_machine.buildings(),
+                                        rf(editor_machine.utilization() * 100.0)
+                                    )
+                                } else {
+                                    format!("{}{} × ", lock, editor_machine.count_text())
+                                }
                             };
                             let tooltip = if (machine.recipe.products.len() == 1
                                 && machine.recipe.name.as_str()
@@ -194,52 +268,25 @@ impl MyApp {
                             } else {
                                 format!("{}({})", machine.crafter.name, machine.recipe.name)
                             };
-                            let modules_text = if machine.modules.is_empty()
-                                && machine.beacons.is_empty()
-                            {
-                                String::new()
-                            } else {
-                                let beacon_text = if machine.beacons.is_empty() {
+                            let modules_text =
+                                if machine.modules.is_empty() && machine.beacons.is_empty() {
                                     String::new()
                                 } else {
-                                    if machine.beacons.iter().all_equal() {
-                                        let modules = module_counts(&machine.beacons[0].modules)
-                                            .into_iter()
-                                            .map(|(name, count)| format!("{count} × {name}"))
-                                            .join(",");
-                                        format!("{} × beacon({})", machine.beacons.len(), modules)
-                                    } else {
-                                        machine
-                                            .beacons
-                                            .iter()
-                                            .map(|beacon| {
-                                                let modules = module_counts(&beacon.modules)
-                                                    .into_iter()
-                                                    .map(|(name, count)| {
-                                                        format!("{count} × {name}")
-                                                    })
-                                                    .join(",");
-                                                format!("beacon({})", modules)
-                                            })
-                                            .join("\n")
-                                    }
-                                };
-                                let beacon_markup = if machine.beacons.is_empty() {
-                                    None
-                                } else {
-                                    Some(format!(
-                                        "{}@[beacon:{}]",
-                                        machine.beacons.len(),
-                                        beacon_text
-                                    ))
+                                    let beacon_markup = machine.beacons.first().map(|beacon| {
+                                        format!(
+                                            "{}@[{}:{}]",
+                                            machine.beacons.len(),
+                                            beacon.type_.name,
+                                            machine.beacon_text()
+                                        )
+                                    });
+                                    let text = module_counts(&machine.modules)
+                                        .into_iter()
+                                        .map(|(name, count)| format!("{count}@[{name}:]"))
+                                        .chain(beacon_markup)
+                                        .join(",");
+                                    format!("[{text}]")
                                 };
-                                let text = module_counts(&machine.modules)
-                                    .into_iter()
-                                    .map(|(name, count)| format!("{count}@[{name}:]"))
-                                    .chain(beacon_markup)
-                                    .join(",");
-                                format!("[{text}]")
-                            };
                             ui.rich_label(format!(
                                 "{}{}@[{}:{}]{}",
                                 if is_first { "" } else { "➡ " },
@@ -248,6 +295,18 @@ impl MyApp {
                                 tooltip,
                                 modules_text
                             ));
+                            let problems = machine
+                                .effect_limits()
+                                .iter()
+                                .map(|l| l.description())
+                                .chain(self.editor.planet_problem(machine))
+                                .collect_vec();
+                            if !problems.is_empty() {
+                                let text = problems.join("\n");
+                                ui.with_tooltip(&text, |ui| {
+                                    ui.colored_label(Color32::from_rgb(200, 100, 0), "⚠")
+                                });
+                            }
                             is_first = true;
                             Frame::new()
                                 .fill(Color32::from_rgb(230, 255, 230))
@@ -255,10 +314,13 @@ impl MyApp {
                                     for stack in &item_speeds {
                                         if stack.speed > Speed::ZERO {
                                             ui.rich_label(format!(
-                                                "{}{} @[{}:]",
+                                                "{}{} @[{}:{}]",
                                                 if is_first { "➡ " } else { "+ " },
-                                                stack.speed,
+                                                self.editor
+                                                    .info()
+                                                    .format_speed(&stack.item, stack.speed),
                                                 stack.item,
+                                                item_cost_tooltip(&self.item_costs, &stack.item),
                                             ));
                                             is_first = false;
                                         }
@@ -272,6 +334,11 @@ impl MyApp {
                             //     self.selected_machine = i;
                             // }
                             ui.add_space(10.0);
+                            show_machine_sensitivity(&self.editor, ui, i);
+                            if self.show_trains {
+                                show_machine_trains(&self.editor, ui, i);
+                            }
+                            ui.add_space(10.0);
                             if machine.crafter.is_source_or_sink() {
                                 let r = ui.with_tooltip("Replace with a crafting machine", |ui| {
                                     ui.button("Craft")
@@ -370,10 +437,15 @@ impl MyApp {
                                             for (menu_item, item_text) in
                                                 &self.replace_with_craft_options
                                             {
+                                                let text = if menu_item.locked() {
+                                                    RichText::new(item_text).weak()
+                                                } else {
+                                                    RichText::new(item_text)
+                                                };
                                                 ui.selectable_value(
                                                     &mut value,
                                                     Some(menu_item),
-                                                    item_text,
+                                                    text,
                                                 );
                                             }
                                         });
@@ -389,18 +461,7 @@ impl MyApp {
                                 // not source or sink
                                 let r = ui.button("Edit");
                                 if r.clicked() {
-                                    self.edit_machine_index = Some(i);
-                                    self.machine_count_constraint = match editor_machine.snippet() {
-                                        MachineSnippet::Source(_) | MachineSnippet::Sink(_) => {
-                                            unreachable!()
-                                        }
-                                        MachineSnippet::Crafter(crafter) => crafter
-                                            .count_constraint
-                                            .map(|c| c.to_string())
-                                            .unwrap_or_default(),
-                                    };
-                                    self.num_beacons = machine.beacons.len().to_string();
-                                    self.focus_machine_constraint_input = true;
+                                    machine_to_edit = Some(i);
                                 }
 
                                 if ui.button("🗙").clicked() {
@@ -409,6 +470,43 @@ impl MyApp {
                             }
                         });
                     }
+                    if let Some(spare) = self
+                        .editor
+                        .spare_capacity()
+                        .filter(|s| !s.outputs.is_empty())
+                    {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(format!("Estimated spare capacity (×{}):", rf(spare.scale)));
+                            for (item, speed) in &spare.outputs {
+                                ui.rich_label(format!(
+                                    "{} @[{item}:]    ",
+                                    self.editor.info().format_speed(item, *speed)
+                                ));
+                            }
+                            if !spare.inputs.is_empty() {
+                                ui.label("using");
+                                for (item, speed) in &spare.inputs {
+                                    ui.rich_label(format!(
+                                        "{} @[{item}:]    ",
+                                        self.editor.info().format_speed(item, *speed)
+                                    ));
+                                }
+                            }
+                        });
+                    }
+                    if !self.editor.machines().is_empty() {
+                        ui.label(format!(
+                            "Power usage: {}",
+                            format_power(self.editor.power_usage())
+                        ));
+                    }
+                    let emissions = self.editor.emissions();
+                    if !emissions.is_empty() {
+                        ui.label(format!("Emissions: {}", format_emissions(&emissions)));
+                    }
+                    if let Some(i) = machine_to_edit {
+                        self.start_editing_machine(i);
+                    }
                     if let Some(i) = index_to_remove {
                         self.saved = false;
                         self.alerts.clear();
@@ -431,6 +529,12 @@ impl MyApp {
                         });
 
                         egui::Frame::group(ui.style()).show(ui, |ui| {
+                            for limit in self.editor.machines()[i].machine().effect_limits() {
+                                ui.colored_label(
+                                    Color32::from_rgb(200, 100, 0),
+                                    format!("⚠ Module effects are wasted: {}", limit.description()),
+                                );
+                            }
                             let crafters = self
                                 .editor
                                 .info()
@@ -453,6 +557,11 @@ impl MyApp {
                                         )
                                         .show_ui(ui, |ui| {
                                             for item in crafters {
+                                                if !self.editor.info().is_crafter_offered(&item)
+                                                    && item != text
+                                                {
+                                                    continue;
+                                                }
                                                 ui.selectable_value(
                                                     &mut text,
                                                     item.clone(),
@@ -583,43 +692,73 @@ impl MyApp {
                                         }
                                     });
                                 }
-                                ui.horizontal(|ui| {
-                                    let label = ui.rich_label(format!(
-                                        "Number of @[beacon:](2@[{}:]) per machine:",
-                                        &self.default_speed_module.name
-                                    ));
-                                    let text_response = TextEdit::singleline(&mut self.num_beacons)
-                                        .desired_width(50.0)
-                                        .ui(ui)
-                                        .labelled_by(label.id);
-                                    if ui.button("Set").clicked()
-                                        || (text_response.lost_focus()
-                                            && ui.input(|i| i.key_pressed(Key::Enter)))
-                                    {
-                                        if let Some(num_beacons) =
-                                            self.num_beacons.parse::<u32>().or_warn()
+                                if self.editor.info().beacons.len() > 1 {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Beacon type:");
+                                        ComboBox::new(("beacon_type", self.generation), "")
+                                            .selected_text(self.beacon_type.as_str())
+                                            .show_ui(ui, |ui| {
+                                                for name in self.editor.info().beacons.keys() {
+                                                    ui.selectable_value(
+                                                        &mut self.beacon_type,
+                                                        name.clone(),
+                                                        name.as_str(),
+                                                    );
+                                                }
+                                            });
+                                    });
+                                }
+                                let beacon_type = self
+                                    .editor
+                                    .info()
+                                    .beacon(Some(&self.beacon_type))
+                                    .cloned()
+                                    .or_warn();
+                                if let Some(beacon_type) = beacon_type {
+                                    ui.horizontal(|ui| {
+                                        let label = ui.rich_label(format!(
+                                            "Number of @[{}:]({}@[{}:]) per machine:",
+                                            beacon_type.name,
+                                            beacon_type.module_inventory_size,
+                                            &self.default_speed_module.name
+                                        ));
+                                        let text_response =
+                                            TextEdit::singleline(&mut self.num_beacons)
+                                                .desired_width(50.0)
+                                                .ui(ui)
+                                                .labelled_by(label.id);
+                                        if ui.button("Set").clicked()
+                                            || (text_response.lost_focus()
+                                                && ui.input(|i| i.key_pressed(Key::Enter)))
                                         {
-                                            self.saved = false;
-                                            self.alerts.clear();
-                                            self.editor
-                                                .set_beacons(
-                                                    i,
-                                                    (0..num_beacons)
-                                                        .map(|_| Beacon {
-                                                            modules: (0..2)
-                                                                .map(|_| {
-                                                                    self.default_speed_module
-                                                                        .clone()
-                                                                })
-                                                                .collect_vec(),
-                                                        })
-                                                        .collect(),
-                                                )
-                                                .or_warn();
-                                            self.after_machines_changed();
+                                            if let Some(num_beacons) =
+                                                self.num_beacons.parse::<u32>().or_warn()
+                                            {
+                                                self.saved = false;
+                                                self.alerts.clear();
+                                                self.editor
+                                                    .set_beacons(
+                                                        i,
+                                                        (0..num_beacons)
+                                                            .map(|_| Beacon {
+                                                                type_: beacon_type.clone(),
+                                                                quality: 0,
+                                                                modules: (0..beacon_type
+                                                                    .module_inventory_size)
+                                                                    .map(|_| {
+                                                                        self.default_speed_module
+                                                                            .clone()
+                                                                    })
+                                                                    .collect_vec(),
+                                                            })
+                                                            .collect(),
+                                                    )
+                                                    .or_warn();
+                                                self.after_machines_changed();
+                                            }
                                         }
-                                    }
-                                });
+                                    });
+                                }
                             }
 
                             if ui.button("Cancel").clicked() {
@@ -629,20 +768,58 @@ impl MyApp {
                     }
                 }
 
-                ui.heading("Constraints");
+                ui.horizontal(|ui| {
+                    ui.heading("Constraints");
+                    ui.add_space(10.0);
+                    ui.label("Rates in:");
+                    let info = self.editor.info();
+                    let mut rate_unit = info.config.rate_unit.clone();
+                    let units = [RateUnit::PerSecond, RateUnit::PerMinute, RateUnit::PerHour]
+                        .into_iter()
+                        .chain(
+                            info.belt_speeds
+                                .iter()
+                                .map(|(_, belt)| RateUnit::Belt(belt.clone())),
+                        )
+                        .collect_vec();
+                    ComboBox::new("rate_unit", "")
+                        .selected_text(rate_unit.suffix())
+                        .show_ui(ui, |ui| {
+                            for unit in units {
+                                let text = unit.suffix().to_string();
+                                ui.selectable_value(&mut rate_unit, unit, text);
+                            }
+                        });
+                    if rate_unit != self.editor.info().config.rate_unit {
+                        self.editor.set_rate_unit(rate_unit).or_warn();
+                        self.item_speed_contraint_speed.clear();
+                        self.generation += 1;
+                    }
+                });
                 egui::Frame::group(ui.style()).show(ui, |ui| {
                     let mut constraint_to_delete = None;
                     let mut any_constraints = false;
                     for (item, speed) in self.editor.item_speed_constraints() {
+                        let speed_text = self.editor.info().format_speed(item, *speed);
                         ui.horizontal(|ui| {
+                            let binding = if self
+                                .editor
+                                .sensitivity()
+                                .is_binding(&Target::ItemProduction(item.clone()))
+                            {
+                                ""
+                            } else {
+                                " (not binding)"
+                            };
                             ui.rich_label(format!(
-                                "@[$lock:Item speed constraint] @[{}]*: {}",
-                                item, speed
+                                "@[$lock:Item speed constraint] @[{}]*: {}{}",
+                                item, speed_text, binding
                             ));
                             if ui.button("Edit").clicked() {
                                 self.item_speed_contraint_item = item.to_string();
                                 self.old_item_speed_contraint_item = item.to_string();
-                                self.item_speed_contraint_speed = speed.to_string();
+                                self.item_speed_contraint_speed =
+                                    self.editor.info().speed_input_text(item, *speed);
                                 focus_speed_constraint_input = true;
                             }
                             if ui.button("🗙").clicked() {
@@ -667,11 +844,21 @@ impl MyApp {
                         }) = machine.snippet()
                         {
                             ui.horizontal(|ui| {
+                                let binding = if self
+                                    .editor
+                                    .sensitivity()
+                                    .is_binding(&Target::MachineCount { machine_index: i })
+                                {
+                                    ""
+                                } else {
+                                    " (not binding)"
+                                };
                                 ui.rich_label(&format!(
-                                    "@[$lock:Machine count constraint] {} × @[{}]*(@[{}]*)",
+                                    "@[$lock:Machine count constraint] {} × @[{}]*(@[{}]*){}",
                                     count,
                                     machine.machine().crafter.name,
                                     machine.machine().recipe.name,
+                                    binding,
                                 ));
                                 if ui.button("Edit").clicked() {
                                     self.edit_machine_index = Some(i);
@@ -725,7 +912,8 @@ impl MyApp {
                         if focus_speed_constraint_input {
                             text_response.request_focus();
                         }
-                        ui.label("/s");
+                        let item = ItemName::from(self.item_speed_contraint_item.as_str());
+                        ui.label(self.editor.info().rate_unit(&item).suffix().to_string());
                         let set = ui.button("Set").clicked();
                         let replace_all = ui.button("Replace all").clicked();
                         if set
@@ -734,14 +922,15 @@ impl MyApp {
                                 && ui.input(|i| i.key_pressed(Key::Enter)))
                         {
                             self.saved = false;
-                            if let Some(speed) = self.item_speed_contraint_speed.parse().or_warn() {
+                            if let Some(speed) = self
+                                .editor
+                                .info()
+                                .parse_speed(&item, &self.item_speed_contraint_speed)
+                                .or_warn()
+                            {
                                 self.alerts.clear();
                                 self.editor
-                                    .set_item_speed_constraint(
-                                        &self.item_speed_contraint_item.as_str().into(),
-                                        Some(speed),
-                                        replace_all,
-                                    )
+                                    .set_item_speed_constraint(&item, Some(speed), replace_all)
                                     .or_warn();
                                 self.after_constraint_changed();
                             }
@@ -750,26 +939,63 @@ impl MyApp {
 
                     ui.horizontal(|ui| {
                         ui.label("Tip:");
-                        for (speed, item) in &self.belt_speeds {
-                            ui.rich_label(format!("@[{item}:] = {speed}    "));
+                        for (speed, item) in &self.editor.info().belt_speeds {
+                            ui.rich_label(format!(
+                                "@[{item}:] = {}    ",
+                                self.editor.info().format_speed(item, *speed)
+                            ));
                         }
                     });
+                    ui.add_space(10.0);
+                    self.show_input_caps(ui);
+                    ui.add_space(10.0);
+                    self.show_scale(ui);
                 });
 
+                self.show_item_policies(ui);
+
                 ui.add_space(10.0);
                 ui.horizontal(|ui| {
                     if ui.button("Open chart").clicked() {
                         self.open_chart().or_warn();
                     }
+                    if ui.button("Open Sankey diagram").clicked() {
+                        self.open_sankey().or_warn();
+                    }
                     if ui.button("Solve again").clicked() {
                         self.alerts.clear();
                         self.after_machines_changed();
                     }
-                    if ui.button("Copy description").clicked() {
-                        self.copy_description().or_warn();
+                    ui.add_space(10.0);
+                    ui.label("Export as:");
+                    ComboBox::new("export_format", "")
+                        .selected_text(self.export_format)
+                        .show_ui(ui, |ui| {
+                            for exporter in exporters() {
+                                ui.selectable_value(
+                                    &mut self.export_format,
+                                    exporter.name(),
+                                    exporter.name(),
+                                );
+                            }
+                        });
+                    if ui.button("Copy").clicked() {
+                        self.copy_export().or_warn();
+                    }
+                    if ui.button("Save to exports").clicked() {
+                        self.save_export().or_warn();
                     }
                 });
 
+                self.show_item_balance(ui);
+                self.show_bill_of_materials(ui);
+                self.show_item_costs(ui);
+                self.show_optimizer(ui);
+                self.show_research(ui);
+                self.show_spoilage(ui);
+                self.show_rockets(ui);
+                self.show_power(ui);
+
                 if !self.alerts.is_empty() {
                     ui.add_space(10.0);
                     ui.horizontal(|ui| {
@@ -0,0 +1,117 @@
+use {
+    super::{app::MyApp, ui_ext::UiExt},
+    crate::{
+        flowchart::{item_flows, transport_text},
+        primitives::Speed,
+        rf,
+    },
+    eframe::egui::{self, Color32, Ui},
+};
+
+impl MyApp {
+    pub fn show_item_balance(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading("Item balance");
+            let text = if self.show_item_balance {
+                "Hide"
+            } else {
+                "Show"
+            };
+            if ui.button(text).clicked() {
+                self.show_item_balance = !self.show_item_balance;
+            }
+        });
+        if !self.show_item_balance {
+            return;
+        }
+        let mut machine_to_edit = None;
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            let flows = item_flows(&self.editor);
+            if flows.is_empty() {
+                ui.label("No items.");
+                return;
+            }
+            for flow in flows {
+                let production: Speed = flow.sources.iter().map(|(_, speed)| *speed).sum();
+                let consumption: Speed = flow.destinations.iter().map(|(_, speed)| *speed).sum();
+                let net = production - consumption;
+                let throughput = production.max(consumption);
+                let transport = if self.editor.info().is_fluid(&flow.item) {
+                    transport_text(self.editor.info(), &flow.item, throughput).0
+                } else if let Some((belt_speed, belt)) = self.editor.info().belt() {
+                    format!("{} @[{belt}:]", rf(throughput / belt_speed))
+                } else {
+                    String::new()
+                };
+                let policy = self
+                    .editor
+                    .item_policies()
+                    .get(&flow.item)
+                    .map(|policy| format!(" [{}]", policy.name()))
+                    .unwrap_or_default();
+                let info = self.editor.info();
+                ui.horizontal(|ui| {
+                    ui.rich_label(format!(
+                        "@[{}]*{policy}: +{} −{} = ",
+                        flow.item,
+                        info.format_speed(&flow.item, production),
+                        info.format_speed(&flow.item, consumption),
+                    ));
+                    let color = if f64::from(net).abs() < 0.001 {
+                        ui.visuals().text_color()
+                    } else {
+                        Color32::from_rgb(200, 100, 0)
+                    };
+                    ui.colored_label(color, info.format_speed(&flow.item, net));
+                    if !transport.is_empty() {
+                        ui.rich_label(format!("(≈ {transport})"));
+                    }
+                });
+                ui.indent(("item_balance", flow.item.as_str()), |ui| {
+                    for (sign, total, machines) in [
+                        ("+", production, &flow.sources),
+                        ("−", consumption, &flow.destinations),
+                    ] {
+                        for (index, speed) in machines {
+                            let editor_machine = &self.editor.machines()[*index];
+                            let machine = editor_machine.machine();
+                            let share = if total > Speed::ZERO {
+                                rf(*speed / total * 100.0)
+                            } else {
+                                0.0
+                            };
+                            ui.horizontal(|ui| {
+                                let text = if machine.crafter.is_source() {
+                                    "source".to_string()
+                                } else if machine.crafter.is_sink() {
+                                    "sink".to_string()
+                                } else {
+                                    format!(
+                                        "{} × @[{}:]@[{}:]",
+                                        editor_machine.count_text(),
+                                        machine.crafter.name,
+                                        machine.recipe.name
+                                    )
+                                };
+                                ui.rich_label(format!(
+                                    "{sign}{} ({share}%) {text}",
+                                    self.editor.info().format_speed(&flow.item, *speed)
+                                ));
+                                if !machine.crafter.is_source_or_sink() {
+                                    let r = ui.with_tooltip("Edit machine", |ui| ui.button("✏"));
+                                    if r.clicked() {
+                                        machine_to_edit = Some(*index);
+                                    }
+                                }
+                            });
+                        }
+                    }
+                });
+            }
+        });
+        if let Some(index) = machine_to_edit {
+            self.start_editing_machine(index);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use {
+    super::{app::MyApp, ui_ext::UiExt},
+    crate::{bom::BillOfMaterials, rf, ResultExtOrWarn},
+    arboard::Clipboard,
+    eframe::egui::{self, Ui},
+};
+
+impl MyApp {
+    fn update_bill_of_materials(&mut self) {
+        if self.bill_of_materials_generation != Some(self.generation) {
+            self.bill_of_materials = BillOfMaterials::new(
+                self.editor.info(),
+                self.editor.machines().iter().map(|m| m.machine()),
+                self.editor.power_plant_buildings(),
+                self.bill_of_materials_logistics,
+            );
+            self.bill_of_materials_generation = Some(self.generation);
+        }
+    }
+
+    pub fn show_bill_of_materials(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading("Bill of materials");
+            let text = if self.show_bill_of_materials {
+                "Hide"
+            } else {
+                "Show"
+            };
+            if ui.button(text).clicked() {
+                self.show_bill_of_materials = !self.show_bill_of_materials;
+            }
+        });
+        if !self.show_bill_of_materials {
+            return;
+        }
+        self.update_bill_of_materials();
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            if ui
+                .checkbox(
+                    &mut self.bill_of_materials_logistics,
+                    "Include belts and inserters (rough estimate)",
+                )
+                .changed()
+            {
+                self.bill_of_materials_generation = None;
+            }
+            let bom = &self.bill_of_materials;
+            if bom.entities.is_empty() {
+                ui.label("Nothing to build.");
+                return;
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Build:");
+                for (item, count) in &bom.entities {
+                    ui.rich_label(format!("{count}@[{item}:]    "));
+                }
+            });
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Raw resources:");
+                for (item, amount) in &bom.raw {
+                    ui.rich_label(format!("{}@[{item}:]    ", rf(*amount)));
+                }
+            });
+            if ui.button("Copy bill of materials").clicked() {
+                Clipboard::new()
+                    .and_then(|mut c| c.set_text(bom.description()))
+                    .or_warn();
+            }
+        });
+    }
+}
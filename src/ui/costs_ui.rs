@@ -0,0 +1,123 @@
+use {
+    super::{app::MyApp, ui_ext::UiExt},
+    crate::{
+        cost::{item_costs, ItemCost},
+        primitives::ItemName,
+        rf,
+    },
+    eframe::egui::{self, Grid, Ui},
+    itertools::Itertools,
+    std::collections::BTreeMap,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostColumn {
+    Item,
+    Raw,
+    CraftingTime,
+    Energy,
+}
+
+pub fn format_energy(joules: f64) -> String {
+    if joules >= 1e9 {
+        format!("{} GJ", rf(joules / 1e9))
+    } else if joules >= 1e6 {
+        format!("{} MJ", rf(joules / 1e6))
+    } else {
+        format!("{} kJ", rf(joules / 1e3))
+    }
+}
+
+fn raw_text(cost: &ItemCost) -> String {
+    cost.raw
+        .iter()
+        .map(|(item, amount)| format!("{} {}", rf(*amount), item))
+        .join(" + ")
+}
+
+/// Tooltip text for an item icon. Must not contain ':' and ']' (see `UiExt::rich_label`).
+pub fn item_cost_tooltip(costs: &BTreeMap<ItemName, ItemCost>, item: &ItemName) -> String {
+    match costs.get(item) {
+        Some(cost) if !cost.raw.contains_key(item) => format!(
+            "{item}\nper unit = {}\n{} machine-seconds, {}",
+            raw_text(cost),
+            rf(cost.crafting_time),
+            format_energy(cost.energy)
+        ),
+        _ => item.to_string(),
+    }
+}
+
+impl MyApp {
+    pub fn update_item_costs(&mut self) {
+        if self.item_costs_generation != Some(self.generation) {
+            self.item_costs = item_costs(&self.editor, &self.editor.added_items());
+            self.item_costs_generation = Some(self.generation);
+        }
+    }
+
+    pub fn show_item_costs(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading("Item costs");
+            let text = if self.show_item_costs { "Hide" } else { "Show" };
+            if ui.button(text).clicked() {
+                self.show_item_costs = !self.show_item_costs;
+            }
+        });
+        if !self.show_item_costs {
+            return;
+        }
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            let (sort_column, descending) = self.item_costs_sort;
+            let mut rows = self.item_costs.iter().collect_vec();
+            rows.sort_by(|(a_item, a), (b_item, b)| {
+                let ordering = match sort_column {
+                    CostColumn::Item => a_item.cmp(b_item),
+                    CostColumn::Raw => a.total_raw().total_cmp(&b.total_raw()),
+                    CostColumn::CraftingTime => a.crafting_time.total_cmp(&b.crafting_time),
+                    CostColumn::Energy => a.energy.total_cmp(&b.energy),
+                };
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+            let mut new_sort = None;
+            Grid::new("item_costs").striped(true).show(ui, |ui| {
+                for (column, title) in [
+                    (CostColumn::Item, "Item"),
+                    (CostColumn::Raw, "Raw resources per unit"),
+                    (CostColumn::CraftingTime, "Machine-seconds"),
+                    (CostColumn::Energy, "Energy"),
+                ] {
+                    let arrow = match (column == sort_column, descending) {
+                        (false, _) => "",
+                        (true, false) => " ⏶",
+                        (true, true) => " ⏷",
+                    };
+                    if ui.button(format!("{title}{arrow}")).clicked() {
+                        new_sort = Some((column, column == sort_column && !descending));
+                    }
+                }
+                ui.end_row();
+                for (item, cost) in rows {
+                    ui.rich_label(format!("@[{item}]*"));
+                    ui.rich_label(
+                        cost.raw
+                            .iter()
+                            .map(|(item, amount)| format!("{}@[{item}:]", rf(*amount)))
+                            .join(" + "),
+                    );
+                    ui.label(rf(cost.crafting_time).to_string());
+                    ui.label(format_energy(cost.energy));
+                    ui.end_row();
+                }
+            });
+            if let Some(sort) = new_sort {
+                self.item_costs_sort = sort;
+            }
+        });
+    }
+}
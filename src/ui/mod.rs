@@ -11,8 +11,20 @@ use {
 
 pub mod app;
 pub mod app_ui;
+pub mod balance_ui;
+pub mod bom_ui;
+pub mod costs_ui;
 pub mod drop_down;
+pub mod optimizer_ui;
+pub mod policies_ui;
+pub mod power_ui;
+pub mod research_ui;
+pub mod rocket_ui;
+pub mod scale_ui;
+pub mod sensitivity_ui;
+pub mod spoilage_ui;
 pub mod tracing_layer;
+pub mod train_ui;
 pub mod ui_ext;
 
 pub fn run() -> anyhow::Result<()> {
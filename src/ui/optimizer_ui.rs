@@ -0,0 +1,147 @@
+use {
+    super::{app::MyApp, ui_ext::UiExt},
+    crate::{
+        module_counts,
+        optimizer::{optimize, Objective, Score},
+        power::format_power,
+        rf, ResultExtOrWarn,
+    },
+    eframe::egui::{self, ComboBox, Grid, TextEdit, Ui},
+    itertools::Itertools,
+};
+
+fn score_text(score: &Score) -> String {
+    format!(
+        "{} buildings, {}, {}/s raw input, {} tiles",
+        rf(score.buildings),
+        format_power(score.power),
+        rf(score.raw_input),
+        rf(score.footprint)
+    )
+}
+
+impl MyApp {
+    pub fn show_optimizer(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading("Module optimizer");
+            let text = if self.show_optimizer { "Hide" } else { "Show" };
+            if ui.button(text).clicked() {
+                self.show_optimizer = !self.show_optimizer;
+            }
+        });
+        if !self.show_optimizer {
+            return;
+        }
+        if self
+            .optimization
+            .as_ref()
+            .is_some_and(|(_, generation)| *generation != self.generation)
+        {
+            self.optimization = None;
+        }
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Minimize:");
+                ComboBox::new("optimizer_objective", "")
+                    .selected_text(self.optimizer_objective.name())
+                    .show_ui(ui, |ui| {
+                        for objective in Objective::ALL {
+                            ui.selectable_value(
+                                &mut self.optimizer_objective,
+                                objective,
+                                objective.name(),
+                            );
+                        }
+                    });
+                ui.label("Max beacons per machine:");
+                TextEdit::singleline(&mut self.optimizer_max_beacons)
+                    .desired_width(30.0)
+                    .show(ui);
+                if ui.button("Optimize").clicked() {
+                    match self.optimizer_max_beacons.trim().parse::<usize>() {
+                        Ok(max_beacons) => {
+                            let info = self.editor.info();
+                            if let Some(beacon_type) =
+                                info.beacon(Some(&self.beacon_type)).or_warn()
+                            {
+                                let optimization = optimize(
+                                    &self.editor,
+                                    self.optimizer_objective,
+                                    &info.modules.values().cloned().collect_vec(),
+                                    beacon_type,
+                                    Some(&self.default_speed_module),
+                                    max_beacons,
+                                );
+                                self.optimization = Some((optimization, self.generation));
+                            }
+                        }
+                        Err(err) => {
+                            tracing::warn!("invalid beacon count: {err}");
+                        }
+                    }
+                }
+            });
+            let Some((optimization, _)) = &self.optimization else {
+                return;
+            };
+            if optimization.machines.is_empty() {
+                ui.label("All machines are already optimal.");
+                return;
+            }
+            let mut apply = false;
+            Grid::new("optimizer").striped(true).show(ui, |ui| {
+                ui.label("Machine");
+                ui.label("Modules");
+                ui.label("Beacons");
+                ui.end_row();
+                for item in &optimization.machines {
+                    let machine = self.editor.machines()[item.machine_index].machine();
+                    ui.rich_label(format!(
+                        "@[{}:]@[{}:]",
+                        machine.crafter.name, machine.recipe.name
+                    ));
+                    ui.rich_label(
+                        module_counts(&item.modules)
+                            .into_iter()
+                            .map(|(name, count)| format!("{count}@[{name}:]"))
+                            .join(","),
+                    );
+                    ui.rich_label(
+                        item.beacons
+                            .first()
+                            .map(|beacon| {
+                                let modules = module_counts(&beacon.modules)
+                                    .into_iter()
+                                    .map(|(name, count)| format!("{count}@[{name}:]"))
+                                    .join(",");
+                                format!(
+                                    "{}@[{}:][{modules}]",
+                                    item.beacons.len(),
+                                    beacon.type_.name
+                                )
+                            })
+                            .unwrap_or_default(),
+                    );
+                    ui.end_row();
+                }
+            });
+            ui.label(format!("Before: {}", score_text(&optimization.before)));
+            ui.label(format!("After: {}", score_text(&optimization.after)));
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() {
+                    apply = true;
+                }
+                if ui.button("Discard").clicked() {
+                    self.optimization = None;
+                }
+            });
+            if apply {
+                if let Some((optimization, _)) = self.optimization.take() {
+                    self.editor.apply_optimization(&optimization).or_warn();
+                    self.after_machines_changed();
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,68 @@
+use {
+    super::{app::MyApp, ui_ext::UiExt},
+    crate::{snippet::ItemPolicy, ResultExtOrWarn},
+    eframe::egui::{self, ComboBox, Ui},
+};
+
+impl MyApp {
+    pub fn show_item_policies(&mut self, ui: &mut Ui) {
+        ui.heading("Item policies");
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            let mut policy_to_delete = None;
+            for (item, policy) in self.editor.item_policies() {
+                ui.horizontal(|ui| {
+                    ui.rich_label(format!("@[{item}]*: {}", policy.name()));
+                    if ui.button("Edit").clicked() {
+                        self.item_policy_item = item.to_string();
+                        self.item_policy = *policy;
+                    }
+                    if ui.button("🗙").clicked() {
+                        policy_to_delete = Some(item.clone());
+                    }
+                });
+            }
+            if let Some(item) = policy_to_delete {
+                self.saved = false;
+                self.alerts.clear();
+                self.editor.set_item_policy(&item, None).or_warn();
+                self.after_machines_changed();
+            }
+            if !self.editor.item_policies().is_empty() {
+                ui.add_space(10.0);
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Set item policy: ");
+                ComboBox::new(("item_policy_item", self.generation), "")
+                    .selected_text(&self.item_policy_item)
+                    .show_ui(ui, |ui| {
+                        for item in self.editor.added_items() {
+                            ui.selectable_value(
+                                &mut self.item_policy_item,
+                                item.to_string(),
+                                item.as_str(),
+                            );
+                        }
+                    });
+                ComboBox::new("item_policy", "")
+                    .selected_text(self.item_policy.name())
+                    .show_ui(ui, |ui| {
+                        for policy in ItemPolicy::ALL {
+                            ui.selectable_value(&mut self.item_policy, policy, policy.name());
+                        }
+                    });
+                if ui.button("Set").clicked() && !self.item_policy_item.is_empty() {
+                    self.saved = false;
+                    self.alerts.clear();
+                    self.editor
+                        .set_item_policy(
+                            &self.item_policy_item.as_str().into(),
+                            Some(self.item_policy),
+                        )
+                        .or_warn();
+                    self.after_machines_changed();
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,117 @@
+use {
+    super::app::MyApp,
+    crate::{
+        power::{self, format_power, PowerSource},
+        rf, ResultExtOrWarn,
+    },
+    anyhow::Context,
+    eframe::egui::{self, Color32, ComboBox, TextEdit, Ui},
+};
+
+fn parse_megawatts(text: &str) -> anyhow::Result<f64> {
+    let megawatts = text
+        .trim()
+        .parse::<f64>()
+        .with_context(|| format!("invalid power: {text:?}"))?;
+    Ok(megawatts * 1e6)
+}
+
+impl MyApp {
+    pub fn show_power(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading("Power generation");
+            let text = if self.show_power { "Hide" } else { "Show" };
+            if ui.button(text).clicked() {
+                self.show_power = !self.show_power;
+            }
+        });
+        if !self.show_power {
+            return;
+        }
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            let usage = self.editor.power_usage();
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Power usage of the snippet: {}",
+                    format_power(usage)
+                ));
+                if ui.button("Use").clicked() {
+                    self.power_demand = rf(usage / 1e6).to_string();
+                }
+            });
+
+            let mut plant_to_delete = None;
+            for (source, power) in self.editor.power_plants() {
+                ui.horizontal(|ui| {
+                    let text = match power::plan(self.editor.info(), *source, *power) {
+                        Ok(plan) => plan.buildings_text(),
+                        Err(err) => err.to_string(),
+                    };
+                    ui.label(format!(
+                        "{} power ({}): {text}",
+                        source.name(),
+                        format_power(*power)
+                    ));
+                    if ui.button("🗙").clicked() {
+                        plant_to_delete = Some(*source);
+                    }
+                });
+            }
+            if let Some(source) = plant_to_delete {
+                self.saved = false;
+                self.alerts.clear();
+                self.editor.set_power_plant(source, None).or_warn();
+                self.after_constraint_changed();
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Generate");
+                TextEdit::singleline(&mut self.power_demand)
+                    .desired_width(50.0)
+                    .show(ui);
+                ui.label("MW with");
+                ComboBox::new("power_source", "")
+                    .selected_text(self.power_source.name())
+                    .show_ui(ui, |ui| {
+                        for source in PowerSource::ALL {
+                            ui.selectable_value(&mut self.power_source, source, source.name());
+                        }
+                    });
+            });
+            if self.power_demand.trim().is_empty() {
+                return;
+            }
+            let plan = parse_megawatts(&self.power_demand)
+                .and_then(|power| power::plan(self.editor.info(), self.power_source, power));
+            let plan = match plan {
+                Ok(plan) => plan,
+                Err(err) => {
+                    ui.colored_label(Color32::from_rgb(200, 100, 0), format!("⚠ {err}"));
+                    return;
+                }
+            };
+            for (name, count) in &plan.buildings {
+                ui.label(format!("    {count} × {name}"));
+            }
+            for note in &plan.notes {
+                ui.label(format!("    {note}"));
+            }
+            if ui
+                .button("Add to snippet")
+                .on_hover_text(
+                    "Adds the buildings to the bill of materials. \
+                    The plant takes water from its own offshore pumps.",
+                )
+                .clicked()
+            {
+                self.saved = false;
+                self.alerts.clear();
+                self.editor
+                    .set_power_plant(plan.source, Some(plan.power))
+                    .or_warn();
+                self.after_constraint_changed();
+            }
+        });
+    }
+}
@@ -0,0 +1,123 @@
+use {
+    super::app::MyApp,
+    crate::{config::RecipeFilter, ResultExtOrWarn},
+    eframe::egui::{self, ComboBox, TextEdit, Ui},
+};
+
+impl MyApp {
+    pub fn show_research(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading("Research");
+            let text = if self.show_research { "Hide" } else { "Show" };
+            if ui.button(text).clicked() {
+                self.show_research = !self.show_research;
+            }
+        });
+        if !self.show_research {
+            return;
+        }
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Recipes in menus:");
+                let mut filter = self.editor.info().config.recipe_filter;
+                ComboBox::new("recipe_filter", "")
+                    .selected_text(filter.name())
+                    .show_ui(ui, |ui| {
+                        for value in RecipeFilter::ALL {
+                            ui.selectable_value(&mut filter, value, value.name());
+                        }
+                    });
+                if filter != self.editor.info().config.recipe_filter {
+                    self.editor.set_recipe_filter(filter);
+                    self.update_recipe_menu_items();
+                }
+            });
+            if self.editor.info().game_data.technologies.is_empty() {
+                ui.label(
+                    "Game data doesn't contain technologies. \
+                    Export it again with export_game_data.lua to plan research.",
+                );
+                return;
+            }
+            let current = &self.editor.info().config.research;
+            if current.is_empty() {
+                ui.label("Current research (config.toml): none");
+            } else {
+                ui.label("Current research (config.toml):");
+                for (technology, level) in current {
+                    ui.label(format!("    {technology}: {level}"));
+                }
+            }
+
+            ui.add_space(10.0);
+            ui.label("Planned research:");
+            let mut technology_to_delete = None;
+            for (technology, level) in self.editor.planned_research() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("    {technology}: {level}"));
+                    if ui.button("Edit").clicked() {
+                        self.research_technology = technology.to_string();
+                        self.research_level = level.to_string();
+                    }
+                    if ui.button("🗙").clicked() {
+                        technology_to_delete = Some(technology.clone());
+                    }
+                });
+            }
+            if let Some(technology) = technology_to_delete {
+                self.saved = false;
+                self.alerts.clear();
+                self.editor
+                    .set_planned_research(&technology, None)
+                    .or_warn();
+                self.update_recipe_menu_items();
+                self.after_machines_changed();
+            }
+            ui.horizontal(|ui| {
+                ui.label("Plan:");
+                let technologies = self.editor.info().game_data.technologies.keys();
+                ComboBox::new(("research_technology", self.generation), "")
+                    .selected_text(&self.research_technology)
+                    .show_ui(ui, |ui| {
+                        for technology in technologies {
+                            ui.selectable_value(
+                                &mut self.research_technology,
+                                technology.to_string(),
+                                technology.as_str(),
+                            );
+                        }
+                    });
+                ui.label("level");
+                TextEdit::singleline(&mut self.research_level)
+                    .desired_width(30.0)
+                    .show(ui);
+                if ui.button("Set").clicked() && !self.research_technology.is_empty() {
+                    if let Some(level) = self.research_level.trim().parse::<u32>().or_warn() {
+                        self.saved = false;
+                        self.alerts.clear();
+                        self.editor
+                            .set_planned_research(
+                                &self.research_technology.as_str().into(),
+                                Some(level),
+                            )
+                            .or_warn();
+                        self.update_recipe_menu_items();
+                        self.after_machines_changed();
+                    }
+                }
+            });
+
+            let mut use_planned_research = self.editor.use_planned_research();
+            if ui
+                .checkbox(&mut use_planned_research, "Show after planned research")
+                .changed()
+            {
+                self.saved = false;
+                self.editor.set_use_planned_research(use_planned_research);
+                self.update_recipe_menu_items();
+                self.after_machines_changed();
+            }
+        });
+    }
+}
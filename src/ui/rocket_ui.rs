@@ -0,0 +1,145 @@
+use {
+    super::app::MyApp,
+    crate::{
+        primitives::ItemName,
+        rf,
+        rocket::{self, ROCKET_PART},
+        ResultExtOrWarn,
+    },
+    eframe::egui::{self, Color32, ComboBox, TextEdit, Ui},
+    itertools::Itertools,
+};
+
+impl MyApp {
+    pub fn show_rockets(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading("Rockets");
+            let text = if self.show_rockets { "Hide" } else { "Show" };
+            if ui.button(text).clicked() {
+                self.show_rockets = !self.show_rockets;
+            }
+        });
+        if !self.show_rockets {
+            return;
+        }
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            let mut shipment_to_delete = None;
+            for (item, speed) in self.editor.rocket_shipments() {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "Ship {} {item}",
+                        self.editor.info().format_speed(item, *speed)
+                    ));
+                    if ui.button("Edit").clicked() {
+                        self.rocket_item = item.to_string();
+                        self.rocket_speed = self.editor.info().speed_input_text(item, *speed);
+                    }
+                    if ui.button("🗙").clicked() {
+                        shipment_to_delete = Some(item.clone());
+                    }
+                });
+            }
+            if let Some(item) = shipment_to_delete {
+                self.saved = false;
+                self.alerts.clear();
+                self.editor.set_rocket_shipment(&item, None).or_warn();
+                self.after_constraint_changed();
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Ship:");
+                ComboBox::new(("rocket_item", self.generation), "")
+                    .selected_text(&self.rocket_item)
+                    .show_ui(ui, |ui| {
+                        for item in self.editor.added_items() {
+                            ui.selectable_value(
+                                &mut self.rocket_item,
+                                item.to_string(),
+                                item.as_str(),
+                            );
+                        }
+                    });
+                TextEdit::singleline(&mut self.rocket_speed)
+                    .desired_width(50.0)
+                    .show(ui);
+                let item = ItemName::from(self.rocket_item.as_str());
+                ui.label(self.editor.info().rate_unit(&item).suffix().to_string());
+                if ui.button("Set").clicked() && !self.rocket_item.is_empty() {
+                    if let Some(speed) = self
+                        .editor
+                        .info()
+                        .parse_speed(&item, &self.rocket_speed)
+                        .or_warn()
+                    {
+                        self.saved = false;
+                        self.alerts.clear();
+                        self.editor
+                            .set_rocket_shipment(&item, Some(speed))
+                            .or_warn();
+                        self.after_constraint_changed();
+                    }
+                }
+                if ui.button("Ship all outputs").clicked() {
+                    let outputs = self
+                        .editor
+                        .machines()
+                        .iter()
+                        .filter(|m| m.machine().crafter.is_sink())
+                        .flat_map(|m| m.machine().item_speeds().collect_vec())
+                        .collect_vec();
+                    self.saved = false;
+                    self.alerts.clear();
+                    for output in outputs {
+                        self.editor
+                            .set_rocket_shipment(&output.item, Some(-output.speed))
+                            .or_warn();
+                    }
+                    self.after_constraint_changed();
+                }
+            });
+
+            if self.editor.rocket_shipments().is_empty() {
+                return;
+            }
+            let plan = match rocket::plan(self.editor.info(), self.editor.rocket_shipments()) {
+                Ok(plan) => plan,
+                Err(err) => {
+                    ui.colored_label(Color32::from_rgb(200, 100, 0), format!("⚠ {err}"));
+                    return;
+                }
+            };
+            for cargo in &plan.cargo {
+                ui.label(format!(
+                    "{} {}: {} per rocket ({} kg each), {} rockets per minute",
+                    self.editor.info().format_speed(&cargo.item, cargo.speed),
+                    cargo.item,
+                    cargo.items_per_rocket,
+                    rf(cargo.weight / 1000.0),
+                    rf(cargo.rockets_per_second * 60.0)
+                ));
+            }
+            let rocket_part = ItemName::from(ROCKET_PART);
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Estimated total: {} rockets per minute, {} {ROCKET_PART} ({} per rocket)",
+                    rf(plan.rockets_per_minute()),
+                    self.editor
+                        .info()
+                        .format_speed(&rocket_part, plan.rocket_parts),
+                    plan.parts_per_rocket
+                ));
+                if self.editor.info().all_items.contains(&rocket_part)
+                    && ui.button("Produce rocket parts").clicked()
+                {
+                    self.saved = false;
+                    self.alerts.clear();
+                    self.editor
+                        .set_item_speed_constraint(&rocket_part, Some(plan.rocket_parts), false)
+                        .or_warn();
+                    self.after_constraint_changed();
+                }
+            });
+        });
+    }
+}
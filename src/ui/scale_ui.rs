@@ -0,0 +1,180 @@
+use {
+    super::{app::MyApp, ui_ext::UiExt},
+    crate::{editor::ScaleLimit, primitives::ItemName, rf, sensitivity::Target, ResultExtOrWarn},
+    eframe::egui::{ComboBox, TextEdit, Ui},
+    tracing::info,
+};
+
+impl MyApp {
+    fn fit_target_text(&self, machine_index: usize) -> String {
+        let Some(editor_machine) = self.editor.machines().get(machine_index) else {
+            return String::new();
+        };
+        let machine = editor_machine.machine();
+        if machine.crafter.is_source() {
+            format!("input of {}", machine.recipe.products[0].name)
+        } else {
+            format!(
+                "count of {} ({})",
+                machine.crafter.name, machine.recipe.name
+            )
+        }
+    }
+
+    /// Input caps list and controls, shown in the constraints group.
+    pub fn show_input_caps(&mut self, ui: &mut Ui) {
+        let mut cap_to_delete = None;
+        for (item, cap) in self.editor.input_caps() {
+            ui.horizontal(|ui| {
+                let binding = if self
+                    .editor
+                    .sensitivity()
+                    .is_binding(&Target::InputCap(item.clone()))
+                {
+                    " (binding)"
+                } else {
+                    ""
+                };
+                ui.rich_label(format!(
+                    "@[$lock:Input cap] @[{item}]* ≤ {}{binding}",
+                    self.editor.info().format_speed(item, *cap)
+                ));
+                if ui.button("Edit").clicked() {
+                    self.input_cap_item = item.to_string();
+                    self.input_cap = self.editor.info().speed_input_text(item, *cap);
+                }
+                if ui.button("🗙").clicked() {
+                    cap_to_delete = Some(item.clone());
+                }
+            });
+        }
+        if let Some(item) = cap_to_delete {
+            self.saved = false;
+            self.alerts.clear();
+            self.editor.set_input_cap(&item, None).or_warn();
+            self.after_constraint_changed();
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Set input cap: ");
+            let inputs = self
+                .editor
+                .machines()
+                .iter()
+                .filter(|m| m.machine().crafter.is_source())
+                .map(|m| m.machine().recipe.products[0].name.clone())
+                .collect::<Vec<_>>();
+            ComboBox::new(("input_cap_item", self.generation), "")
+                .selected_text(&self.input_cap_item)
+                .show_ui(ui, |ui| {
+                    for item in inputs {
+                        ui.selectable_value(
+                            &mut self.input_cap_item,
+                            item.to_string(),
+                            item.as_str(),
+                        );
+                    }
+                });
+            ui.label("at most");
+            TextEdit::singleline(&mut self.input_cap)
+                .desired_width(50.0)
+                .show(ui);
+            let item = ItemName::from(self.input_cap_item.as_str());
+            ui.label(self.editor.info().rate_unit(&item).suffix().to_string());
+            if ui.button("Set").clicked() && !self.input_cap_item.is_empty() {
+                if let Some(cap) = self
+                    .editor
+                    .info()
+                    .parse_speed(&item, &self.input_cap)
+                    .or_warn()
+                {
+                    self.saved = false;
+                    self.alerts.clear();
+                    self.editor.set_input_cap(&item, Some(cap)).or_warn();
+                    self.after_constraint_changed();
+                }
+            }
+        });
+    }
+
+    /// Scale and "fit to" controls, shown in the constraints group.
+    pub fn show_scale(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Scale all constraints and caps by:");
+            TextEdit::singleline(&mut self.scale_factor)
+                .desired_width(50.0)
+                .show(ui);
+            if ui.button("Scale").clicked() {
+                if let Some(factor) = self.scale_factor.trim().parse::<f64>().or_warn() {
+                    self.alerts.clear();
+                    self.saved = false;
+                    self.editor.scale(factor).or_warn();
+                    self.after_constraint_changed();
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Fit to:");
+            let targets = self
+                .editor
+                .machines()
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| !m.machine().crafter.is_sink())
+                .map(|(index, _)| (index, self.fit_target_text(index)))
+                .collect::<Vec<_>>();
+            let selected = self
+                .fit_machine_index
+                .map(|index| self.fit_target_text(index))
+                .unwrap_or_default();
+            ComboBox::new(("fit_target", self.generation), "")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (index, text) in targets {
+                        ui.selectable_value(&mut self.fit_machine_index, Some(index), text);
+                    }
+                });
+            ui.label("at most");
+            TextEdit::singleline(&mut self.fit_limit)
+                .desired_width(50.0)
+                .show(ui);
+            let input = self
+                .fit_machine_index
+                .and_then(|index| self.editor.machines().get(index))
+                .filter(|m| m.machine().crafter.is_source())
+                .map(|m| m.machine().recipe.products[0].name.clone());
+            if let Some(item) = input {
+                ui.label(self.editor.info().rate_unit(&item).suffix().to_string());
+            }
+            if ui.button("Fit").clicked() {
+                self.fit().or_warn();
+            }
+        });
+    }
+
+    fn fit(&mut self) -> anyhow::Result<()> {
+        let index = self
+            .fit_machine_index
+            .filter(|index| *index < self.editor.machines().len())
+            .ok_or_else(|| anyhow::format_err!("select an input or a machine to fit to"))?;
+        let machine = self.editor.machines()[index].machine();
+        let limit = if machine.crafter.is_source() {
+            let item = machine.recipe.products[0].name.clone();
+            ScaleLimit::Input {
+                speed: self.editor.info().parse_speed(&item, &self.fit_limit)?,
+                item,
+            }
+        } else {
+            ScaleLimit::MachineCount {
+                machine_index: index,
+                count: self.fit_limit.trim().parse()?,
+            }
+        };
+        self.alerts.clear();
+        self.saved = false;
+        let factor = self.editor.fit_to(&limit)?;
+        info!("scaled by {}", rf(factor));
+        self.after_constraint_changed();
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use {
+    super::ui_ext::UiExt,
+    crate::{editor::Editor, rf, sensitivity::Target},
+    eframe::egui::Ui,
+    itertools::Itertools,
+};
+
+fn target_delta_text(editor: &Editor, target: &Target, delta: f64) -> String {
+    let sign = if delta >= 0.0 { "+" } else { "" };
+    match target {
+        Target::ItemProduction(item) | Target::InputCap(item) => format!(
+            "{sign}{} {item}",
+            editor.info().format_speed(item, delta.into())
+        ),
+        Target::MachineCount { machine_index } => {
+            let name = editor
+                .machines()
+                .get(*machine_index)
+                .map(|m| m.machine().recipe.name.to_string())
+                .unwrap_or_default();
+            format!("{sign}{} × {name} machines", rf(delta))
+        }
+    }
+}
+
+/// Sensitivity column of the Machines view: how a binding target changes
+/// per one more machine, and unused capacity of the last building.
+pub fn show_machine_sensitivity(editor: &Editor, ui: &mut Ui, machine_index: usize) {
+    let sensitivity = editor.sensitivity();
+    let (Some(machine), Some(editor_machine)) = (
+        sensitivity.machines.get(machine_index),
+        editor.machines().get(machine_index),
+    ) else {
+        return;
+    };
+    let crafter = &editor_machine.machine().crafter;
+    if crafter.is_sink() || !editor.solved() {
+        return;
+    }
+    let per = if crafter.is_source() {
+        format!(
+            "per {} more",
+            editor.info().format_speed(
+                &editor_machine.machine().recipe.products[0].name,
+                1.0.into()
+            )
+        )
+    } else {
+        "per machine".to_string()
+    };
+    let main = machine
+        .target_deltas
+        .iter()
+        .find(|(target, _)| sensitivity.is_binding(target))
+        .or(machine.target_deltas.first());
+    let mut text = main
+        .map(|(target, delta)| format!("Δ {} {per}", target_delta_text(editor, target, *delta)))
+        .unwrap_or_default();
+    if !crafter.is_source() && machine.slack > 0.0 {
+        if !text.is_empty() {
+            text.push_str(", ");
+        }
+        text.push_str(&format!("slack {}", rf(machine.slack)));
+    }
+    if sensitivity.ambiguous {
+        let note = "constraints allow more than one solution, add constraints to see \
+            how targets change";
+        ui.with_tooltip(note, |ui| {
+            if text.is_empty() {
+                ui.label("Δ ?")
+            } else {
+                ui.label(format!("Δ ?, {text}"))
+            }
+        });
+        return;
+    }
+    if text.is_empty() {
+        return;
+    }
+    let tooltip = machine
+        .target_deltas
+        .iter()
+        .map(|(target, delta)| {
+            let binding = if sensitivity.is_binding(target) {
+                ""
+            } else {
+                " (not binding)"
+            };
+            format!("{}{binding}", target_delta_text(editor, target, *delta))
+        })
+        .chain((machine.slack > 0.0).then(|| {
+            format!(
+                "last building is idle {}% of the time",
+                rf(machine.slack * 100.0)
+            )
+        }))
+        .join("\n");
+    ui.with_tooltip(&format!("Change {per}:\n{tooltip}"), |ui| ui.label(text));
+}
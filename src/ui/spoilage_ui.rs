@@ -0,0 +1,117 @@
+use {
+    super::app::MyApp,
+    crate::{editor::DEFAULT_TRANSIT_TIME, primitives::Speed, rf, spoilage, ResultExtOrWarn},
+    anyhow::Context,
+    eframe::egui::{self, Color32, TextEdit, Ui},
+};
+
+fn parse_time(text: &str) -> anyhow::Result<Option<f64>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let value = text
+        .parse::<f64>()
+        .with_context(|| format!("invalid time: {text:?}"))?;
+    Ok(Some(value))
+}
+
+impl MyApp {
+    /// Fills the spoilage inputs from the settings of the snippet.
+    pub fn reset_spoilage_inputs(&mut self) {
+        self.transit_time = self.editor.transit_time().to_string();
+        self.max_latency = self
+            .editor
+            .max_latency()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+    }
+
+    pub fn show_spoilage(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading("Spoilage");
+            let text = if self.show_spoilage { "Hide" } else { "Show" };
+            if ui.button(text).clicked() {
+                self.show_spoilage = !self.show_spoilage;
+            }
+        });
+        if !self.show_spoilage {
+            return;
+        }
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            if self.editor.info().game_data.items.is_empty() {
+                ui.label(
+                    "Game data doesn't contain spoil times. \
+                    Export it again with export_game_data.lua to estimate spoilage.",
+                );
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.label("Transit time between machines (s):");
+                TextEdit::singleline(&mut self.transit_time)
+                    .hint_text(DEFAULT_TRANSIT_TIME.to_string())
+                    .desired_width(50.0)
+                    .show(ui);
+                ui.label("Max latency (s):");
+                TextEdit::singleline(&mut self.max_latency)
+                    .hint_text("none")
+                    .desired_width(50.0)
+                    .show(ui);
+                if ui.button("Set").clicked() {
+                    let settings = parse_time(&self.transit_time).and_then(|transit_time| {
+                        Ok((transit_time, parse_time(&self.max_latency)?))
+                    });
+                    if let Some((transit_time, max_latency)) = settings.or_warn() {
+                        self.alerts.clear();
+                        if self
+                            .editor
+                            .set_spoilage_settings(transit_time, max_latency)
+                            .or_warn()
+                            .is_some()
+                        {
+                            self.saved = false;
+                            self.after_constraint_changed();
+                        }
+                    }
+                }
+            });
+
+            if !self.editor.solved() {
+                return;
+            }
+            let spoilage = spoilage::analyze(&self.editor);
+            if spoilage.is_empty() {
+                ui.label("No spoilable items are transported between machines.");
+                return;
+            }
+            for item in &spoilage {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{}: {}s latency, {}% fresh on arrival (spoils in {}s)",
+                        item.item,
+                        rf(item.latency),
+                        rf(item.freshness.max(0.0) * 100.0),
+                        rf(item.spoil_time)
+                    ));
+                    if item.spoiled > Speed::ZERO {
+                        ui.label(format!(
+                            "{} spoiled",
+                            self.editor.info().format_speed(&item.item, item.spoiled)
+                        ));
+                    }
+                });
+                if let Some(warning) = &item.warning {
+                    ui.colored_label(Color32::from_rgb(200, 100, 0), format!("⚠ {warning}"));
+                }
+            }
+            let byproducts = spoilage::byproducts(&spoilage);
+            for (item, speed) in byproducts {
+                ui.label(format!(
+                    "Byproduct: {} {item}",
+                    self.editor.info().format_speed(&item, speed)
+                ));
+            }
+        });
+    }
+}
@@ -0,0 +1,134 @@
+use {
+    super::{app::MyApp, ui_ext::UiExt},
+    crate::{
+        editor::Editor,
+        rf,
+        train::{train_load, wagon_type, wagons},
+        ResultExtOrWarn,
+    },
+    anyhow::Context,
+    eframe::egui::{Color32, ComboBox, TextEdit, Ui},
+};
+
+/// Train column of the Machines view: wagons and trains that deliver the input of a source
+/// or take away the output of a sink.
+pub fn show_machine_trains(editor: &Editor, ui: &mut Ui, machine_index: usize) {
+    let Some(editor_machine) = editor.machines().get(machine_index) else {
+        return;
+    };
+    let machine = editor_machine.machine();
+    if !machine.crafter.is_source_or_sink() || !editor.solved() {
+        return;
+    }
+    let Some(item_speed) = machine.item_speeds().next() else {
+        return;
+    };
+    match train_load(
+        editor.info(),
+        &item_speed.item,
+        item_speed.speed,
+        editor.train_round_trip_time(),
+        editor.wagons_per_train(),
+        editor.wagon_of_type(wagon_type(editor.info(), &item_speed.item)),
+    ) {
+        Ok(load) => {
+            let tooltip = format!(
+                "{} per wagon\n{} trains per minute\n{} trains on the route",
+                rf(load.wagon_capacity),
+                rf(load.trains_per_minute),
+                load.trains
+            );
+            ui.with_tooltip(&tooltip, |ui| {
+                ui.label(format!(
+                    "🚂 {} wagons/min, {} trains",
+                    rf(load.wagons_per_minute),
+                    load.trains
+                ))
+            });
+        }
+        Err(err) => {
+            ui.with_tooltip(&err.to_string(), |ui| {
+                ui.colored_label(Color32::from_rgb(200, 100, 0), "🚂 ⚠")
+            });
+        }
+    }
+}
+
+impl MyApp {
+    /// Fills the train inputs from the settings of the snippet.
+    pub fn reset_train_inputs(&mut self) {
+        self.train_round_trip_time = self.editor.train_round_trip_time().to_string();
+        self.wagons_per_train = self.editor.wagons_per_train().to_string();
+    }
+
+    pub fn show_train_settings(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Train round trip (s):");
+            TextEdit::singleline(&mut self.train_round_trip_time)
+                .desired_width(50.0)
+                .show(ui);
+            ui.label("Wagons per train:");
+            TextEdit::singleline(&mut self.wagons_per_train)
+                .desired_width(30.0)
+                .show(ui);
+            if ui.button("Set").clicked() {
+                let settings = self
+                    .train_round_trip_time
+                    .trim()
+                    .parse::<f64>()
+                    .with_context(|| format!("invalid time: {:?}", self.train_round_trip_time))
+                    .and_then(|time| {
+                        let wagons =
+                            self.wagons_per_train
+                                .trim()
+                                .parse::<u32>()
+                                .with_context(|| {
+                                    format!("invalid wagon count: {:?}", self.wagons_per_train)
+                                })?;
+                        Ok((time, wagons))
+                    });
+                if let Some((time, wagons)) = settings.or_warn() {
+                    self.alerts.clear();
+                    if self
+                        .editor
+                        .set_train_settings(Some(time), Some(wagons))
+                        .or_warn()
+                        .is_some()
+                    {
+                        self.saved = false;
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            for (label, type_) in [("Wagon:", "cargo-wagon"), ("Fluid wagon:", "fluid-wagon")] {
+                let info = self.editor.info();
+                let options = wagons(info, type_);
+                let Some(default) = options.first() else {
+                    continue;
+                };
+                let selected = self.editor.wagon_of_type(type_).unwrap_or(default);
+                let mut chosen = None;
+                ui.label(label);
+                ComboBox::new(type_, "")
+                    .selected_text(selected.as_str())
+                    .show_ui(ui, |ui| {
+                        for wagon in &options {
+                            if ui
+                                .selectable_label(*wagon == selected, wagon.as_str())
+                                .clicked()
+                            {
+                                chosen = Some((*wagon).clone());
+                            }
+                        }
+                    });
+                if let Some(wagon) = chosen {
+                    self.alerts.clear();
+                    if self.editor.set_wagon(&wagon).or_warn().is_some() {
+                        self.saved = false;
+                    }
+                }
+            }
+        });
+    }
+}